@@ -0,0 +1,129 @@
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, Weekday};
+
+use crate::{reminder::at, Reminder};
+
+// upcoming occurrences of reminders, for remember agenda and remember cal
+
+// a reminder that goes off every minute would otherwise fill the agenda on its own
+const LIMIT: usize = 1000;
+
+// every time the reminder goes off from `from` until before `until`, a snooze first
+pub fn occurrences(
+    reminder: &Reminder,
+    from: DateTime<Local>,
+    until: DateTime<Local>,
+) -> Vec<DateTime<Local>> {
+    let mut times = vec![];
+    if reminder.is_done() {
+        return times;
+    }
+    let mut after = from;
+    if let Some(snoozed) = reminder.snoozed() {
+        if snoozed >= until {
+            return times;
+        }
+        times.push(snoozed);
+        after = after.max(snoozed);
+    } else if let Some(due) = reminder
+        .next_fire_time(reminder.last().unwrap_or(reminder.created()))
+        .filter(|due| *due <= from)
+    {
+        // overdue, it goes off as soon as the daemon sees it
+        times.push(due);
+    }
+    while times.len() < LIMIT {
+        match reminder.next_fire_time(after) {
+            Some(time) if time < until => {
+                times.push(time);
+                after = time;
+            }
+            _ => break,
+        }
+    }
+    times
+}
+
+// a day of the agenda and what goes off on it, in order
+pub type Day<'a> = (NaiveDate, Vec<(DateTime<Local>, &'a Reminder)>);
+
+// occurrences of all the reminders in the next `days` days grouped by day, both in order
+pub fn agenda<'a>(reminders: &[&'a Reminder], now: DateTime<Local>, days: u32) -> Vec<Day<'a>> {
+    let until = now + Duration::days(days as i64);
+    let mut all: Vec<(DateTime<Local>, &Reminder)> = reminders
+        .iter()
+        .flat_map(|r| occurrences(r, now, until).into_iter().map(move |t| (t, *r)))
+        .collect();
+    all.sort_by_key(|(time, _)| *time);
+
+    let mut days: Vec<Day> = vec![];
+    for (time, r) in all {
+        let date = time.naive_local().date();
+        match days.last_mut() {
+            Some((day, list)) if *day == date => list.push((time, r)),
+            _ => days.push((date, vec![(time, r)])),
+        }
+    }
+    days
+}
+
+// in 2h, 3d ago
+pub fn relative(time: DateTime<Local>, now: DateTime<Local>) -> String {
+    let seconds = (time - now).num_seconds();
+    let (n, unit) = match seconds.abs() {
+        s if s < 60 => return String::from("now"),
+        s if s < 60 * 60 => (s / 60, "m"),
+        s if s < 24 * 60 * 60 => (s / (60 * 60), "h"),
+        s => (s / (24 * 60 * 60), "d"),
+    };
+    if seconds < 0 {
+        format!("{}{} ago", n, unit)
+    } else {
+        format!("in {}{}", n, unit)
+    }
+}
+
+// the days of a month that any of the reminders go off on, from `now` on
+pub fn marked(reminders: &[&Reminder], year: i32, month: u32, now: DateTime<Local>) -> Vec<u32> {
+    let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return vec![];
+    };
+    let next = first + Months::new(1);
+    let (Some(from), Some(until)) = (at(&Local, first, 0, 0), at(&Local, next, 0, 0)) else {
+        return vec![];
+    };
+    let mut days: Vec<u32> = reminders
+        .iter()
+        .flat_map(|r| occurrences(r, from.max(now), until))
+        .filter(|time| *time >= from)
+        .map(|time| time.day())
+        .collect();
+    days.sort_unstable();
+    days.dedup();
+    days
+}
+
+// a month like cal(1) starting on monday, days in `marked` get a * after them
+pub fn month(year: i32, month: u32, marked: &[u32]) -> Option<String> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let days = (28..=31)
+        .rev()
+        .find(|d| NaiveDate::from_ymd_opt(year, month, *d).is_some())?;
+
+    let title = format!("{} {}", first.format("%B"), year);
+    let mut out = format!("{:^28}\n", title).trim_end().to_string();
+    out.push('\n');
+    out.push_str(" Mo  Tu  We  Th  Fr  Sa  Su\n");
+    let offset = first.weekday().num_days_from_monday();
+    let mut line = "    ".repeat(offset as usize);
+    for day in 1..=days {
+        let mark = if marked.contains(&day) { '*' } else { ' ' };
+        line.push_str(&format!(" {:>2}{}", day, mark));
+        let weekday = first.with_day(day)?.weekday();
+        if weekday == Weekday::Sun || day == days {
+            out.push_str(line.trim_end());
+            out.push('\n');
+            line.clear();
+        }
+    }
+    Some(out)
+}
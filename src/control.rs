@@ -0,0 +1,323 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Duration as ChronoDuration, Local};
+
+use crate::{
+    filter, parser, reminder,
+    store::{Store, StoreError},
+};
+
+// the daemon listens on a unix socket so other clients (the tui) can change its store while
+// it runs. a client sends one request line, gets the reply and the connection is closed:
+//   list [filter]             -> a line per reminder: id, due, state and statement split by tabs
+//   add <remind statement>    -> the new id
+//   edit <id> <remind statement>
+//   rm <id>
+//   rm where <filter>         -> the ids removed, eg: rm where tag:work and done
+//   done <id>
+//   snooze <id> <duration>    -> eg: snooze 3 10m
+//   snooze <duration> where <filter> -> the ids snoozed
+//   undo                      -> the request it undid, whichever client sent it
+//   redo                      -> the request it made again
+//   journal                   -> a line per request: undo or redo and the request, split by tabs
+//   events [id]               -> a line per change since the log was compacted: time, what
+//                                happened (created, edited, fired, ...) and the id, by tabs
+// the reply starts with "ok" or "error <message>", an ok reply has its result on the lines after
+
+// $XDG_RUNTIME_DIR/remember.sock, or in /tmp when there is no runtime dir
+pub fn socket_path() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("remember.sock"),
+        None => {
+            let user = env::var("USER").unwrap_or_else(|_| String::from("remember"));
+            env::temp_dir().join(format!("remember-{}.sock", user))
+        }
+    }
+}
+
+// runs a request against the store, saving it if anything changed. a request that changes
+// the store is journaled so undo and redo work from any client
+pub fn handle(store: &mut Store, request: &str, now: DateTime<Local>) -> Result<String, String> {
+    let request = request.trim();
+    let (command, rest) = request.split_once(' ').unwrap_or((request, ""));
+    let reply = match command {
+        "list" => return list(store, rest, now),
+        "journal" => {
+            let (done, undone) = store.history();
+            let lines: Vec<String> = done
+                .iter()
+                .map(|r| format!("undo\t{}", r))
+                .chain(undone.iter().map(|r| format!("redo\t{}", r)))
+                .collect();
+            return Ok(lines.join("\n"));
+        }
+        "events" => {
+            let id = match rest.trim() {
+                "" => None,
+                text => Some(text.parse().map_err(|_| format!("invalid id '{}'", text))?),
+            };
+            let lines: Vec<String> = store
+                .events()
+                .iter()
+                .filter(|e| id.is_none_or(|id| e.id == id))
+                .map(|e| format!("{}\t{}\t{}", e.time.to_rfc3339(), e.kind, e.id))
+                .collect();
+            return Ok(lines.join("\n"));
+        }
+        "undo" => store.undo().ok_or("nothing to undo")?,
+        "redo" => store.redo().ok_or("nothing to redo")?,
+        _ => {
+            let before = store.snapshot();
+            let reply = change(store, command, rest, now)?;
+            store.journal(request, before);
+            reply
+        }
+    };
+    store.save().map_err(|e| e.to_string())?;
+    Ok(reply)
+}
+
+fn select(store: &Store, text: &str, now: DateTime<Local>) -> Result<Vec<u32>, String> {
+    let filter = filter::parse(text).map_err(|e| e.to_string())?;
+    Ok(store
+        .iter()
+        .filter(|(_, r)| filter.matches(r, now))
+        .map(|(id, _)| id)
+        .collect())
+}
+
+fn list(store: &Store, rest: &str, now: DateTime<Local>) -> Result<String, String> {
+    let ids = match rest.trim() {
+        "" => None,
+        text => Some(select(store, text, now)?),
+    };
+    let mut entries: Vec<_> = store
+        .iter()
+        .filter(|(id, _)| ids.as_ref().is_none_or(|ids| ids.contains(id)))
+        .collect();
+    entries.sort_by_key(|(id, r)| (r.is_done(), r.due(now).is_none(), r.due(now), *id));
+    let lines: Vec<String> = entries
+        .into_iter()
+        .map(|(id, r)| {
+            let due = match r.due(now) {
+                Some(due) if !r.is_done() => due.to_rfc3339(),
+                _ => String::from("-"),
+            };
+            format!("{}\t{}\t{}\t{}", id, due, state(r), r)
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+// the requests that change the store
+fn change(
+    store: &mut Store,
+    command: &str,
+    rest: &str,
+    now: DateTime<Local>,
+) -> Result<String, String> {
+    let id = |text: &str| -> Result<u32, String> {
+        let id = text.parse().map_err(|_| format!("invalid id '{}'", text))?;
+        match store.get(id) {
+            Some(_) => Ok(id),
+            None => Err(format!("no reminder with id {}", id)),
+        }
+    };
+    Ok(match command {
+        "add" => {
+            let r = parser::parse_reminder(rest).map_err(|e| e.to_string())?;
+            store.add(r).to_string()
+        }
+        "edit" => {
+            let (text, statement) = rest.split_once(' ').unwrap_or((rest, ""));
+            let id = id(text)?;
+            let r = parser::parse_reminder(statement).map_err(|e| e.to_string())?;
+            store.replace(id, r);
+            String::new()
+        }
+        "rm" => match rest.strip_prefix("where ") {
+            Some(text) => {
+                let ids = select(store, text, now)?;
+                for id in &ids {
+                    store.remove(*id);
+                }
+                joined(&ids)
+            }
+            None => {
+                store.remove(id(rest)?);
+                String::new()
+            }
+        },
+        "done" => {
+            if let Some(r) = store.get_mut(id(rest)?) {
+                r.set_done();
+            }
+            String::new()
+        }
+        "snooze" if rest.contains(" where ") => {
+            let (duration, text) = rest.split_once(" where ").unwrap_or_default();
+            let until = now + ChronoDuration::seconds(lead(duration)? as i64);
+            let ids = select(store, text, now)?;
+            for id in &ids {
+                if let Some(r) = store.get_mut(*id) {
+                    r.snooze(until);
+                }
+            }
+            joined(&ids)
+        }
+        "snooze" => {
+            let (text, duration) = rest.split_once(' ').unwrap_or((rest, "10m"));
+            let id = id(text)?;
+            let until = now + ChronoDuration::seconds(lead(duration)? as i64);
+            if let Some(r) = store.get_mut(id) {
+                r.snooze(until);
+            }
+            until.to_rfc3339()
+        }
+        _ => return Err(format!("unknown request '{}'", command)),
+    })
+}
+
+fn lead(duration: &str) -> Result<u32, String> {
+    reminder::parse_lead(duration.trim())
+        .ok_or(format!("invalid duration '{}', eg: 10m", duration.trim()))
+}
+
+fn joined(ids: &[u32]) -> String {
+    let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    ids.join(" ")
+}
+
+pub fn state(r: &reminder::Reminder) -> &'static str {
+    if r.is_done() {
+        "done"
+    } else if r.snoozed().is_some() {
+        "snoozed"
+    } else {
+        "active"
+    }
+}
+
+fn encode(reply: Result<String, String>) -> String {
+    match reply {
+        Ok(text) if text.is_empty() => String::from("ok\n"),
+        Ok(text) => format!("ok\n{}\n", text),
+        Err(e) => format!("error {}\n", e.replace('\n', " ")),
+    }
+}
+
+fn decode(reply: &str) -> Result<String, String> {
+    match reply.split_once('\n').unwrap_or((reply, "")) {
+        ("ok", rest) => Ok(rest.trim_end_matches('\n').to_string()),
+        (line, _) => Err(line.strip_prefix("error ").unwrap_or(line).to_string()),
+    }
+}
+
+pub struct Server {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl Server {
+    // fails if another daemon is already listening on the socket, a stale one is replaced
+    pub fn bind(path: &Path) -> io::Result<Server> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!(
+                    "{} is in use, is the daemon already running?",
+                    path.display()
+                ),
+            ));
+        }
+        let _ = fs::remove_file(path);
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Server {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    // answers every client that is waiting, returns whether any request was handled
+    pub fn poll(&self, store: &mut Store) -> io::Result<bool> {
+        let mut handled = false;
+        loop {
+            let mut stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(handled),
+                Err(e) => return Err(e),
+            };
+            stream.set_nonblocking(false)?;
+            stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+            let mut request = String::new();
+            if BufReader::new(&stream).read_line(&mut request).is_err() {
+                continue;
+            }
+            let reply = handle(store, &request, Local::now());
+            let _ = stream.write_all(encode(reply).as_bytes());
+            handled = true;
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// sends a request to the daemon, Err(io) if there is no daemon to talk to
+pub fn request(path: &Path, request: &str) -> io::Result<Result<String, String>> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.write_all(format!("{}\n", request.replace('\n', " ")).as_bytes())?;
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply)?;
+    Ok(decode(&reply))
+}
+
+// the daemon if one is running, otherwise the store itself and the daemon's socket
+pub enum Control {
+    Daemon(PathBuf),
+    Store(Store, PathBuf),
+}
+
+impl Control {
+    pub fn connect(socket: PathBuf, store: PathBuf) -> Result<Control, StoreError> {
+        if UnixStream::connect(&socket).is_ok() {
+            Ok(Control::Daemon(socket))
+        } else {
+            Ok(Control::Store(Store::open(store)?, socket))
+        }
+    }
+
+    pub fn request(&mut self, request: &str) -> Result<String, String> {
+        match self {
+            Control::Daemon(path) => match self::request(path, request) {
+                Ok(reply) => reply,
+                Err(e) => Err(format!("lost the daemon: {}", e)),
+            },
+            Control::Store(store, socket) => {
+                // a daemon started since holds the lock for as long as it runs, it is asked instead
+                let lock = match store.try_lock().map_err(|e| e.to_string())? {
+                    Some(lock) => lock,
+                    None if UnixStream::connect(&socket).is_ok() => {
+                        *self = Control::Daemon(socket.clone());
+                        return self.request(request);
+                    }
+                    None => store.lock().map_err(|e| e.to_string())?,
+                };
+                let reply = handle(store, request, Local::now());
+                drop(lock);
+                reply
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+
+use crate::reminder::at;
+
+// a 5 field cron expression, minute hour day-of-month month day-of-week. each field is kept
+// as a bit set of the values it matches
+
+const ALIASES: [(&str, &str); 7] = [
+    ("@yearly", "0 0 1 1 *"),
+    ("@annually", "0 0 1 1 *"),
+    ("@monthly", "0 0 1 * *"),
+    ("@weekly", "0 0 * * 0"),
+    ("@daily", "0 0 * * *"),
+    ("@midnight", "0 0 * * *"),
+    ("@hourly", "0 * * * *"),
+];
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+// name, min, max and the names that can be used instead of numbers
+const FIELDS: [(&str, u32, u32, &[&str]); 5] = [
+    ("minute", 0, 59, &[]),
+    ("hour", 0, 23, &[]),
+    ("day of month", 1, 31, &[]),
+    ("month", 1, 12, &MONTHS),
+    ("day of week", 0, 7, &WEEKDAYS), //0 and 7 are both sunday
+];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Cron {
+    text: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,     //day of month starts with *
+    any_weekday: bool, //day of week starts with *
+}
+
+impl Cron {
+    // the first minute after `after` that matches, or None if no day ever does
+    pub fn next_fire_time(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut date = after.naive_local().date();
+        // leap days can be 8 years apart
+        for _ in 0..366 * 8 {
+            if self.matches(date) {
+                for hour in bits(self.hours) {
+                    for minute in bits(self.minutes) {
+                        if let Some(time) = at(&Local, date, hour, minute).filter(|t| *t > after) {
+                            return Some(time);
+                        }
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    // like cron, when both day fields are restricted a day matching either is enough
+    fn matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & 1 << date.day() != 0;
+        let weekday = self.weekdays & 1 << date.weekday().num_days_from_sunday() != 0;
+        self.months & 1 << date.month() != 0
+            && if self.either_day() {
+                day || weekday
+            } else {
+                day && weekday
+            }
+    }
+
+    pub fn either_day(&self) -> bool {
+        !self.any_day && !self.any_weekday
+    }
+
+    pub fn minutes(&self) -> Vec<u32> {
+        bits(self.minutes).collect()
+    }
+
+    pub fn hours(&self) -> Vec<u32> {
+        bits(self.hours).collect()
+    }
+
+    pub fn days(&self) -> Vec<u32> {
+        bits(self.days).collect()
+    }
+
+    pub fn months(&self) -> Vec<u32> {
+        bits(self.months).collect()
+    }
+
+    // 0 is sunday
+    pub fn weekdays(&self) -> Vec<u32> {
+        bits(self.weekdays).collect()
+    }
+}
+
+// the error is the byte offset in the expression along with what is wrong there
+impl FromStr for Cron {
+    type Err = (usize, String);
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let expr = if text.starts_with('@') {
+            ALIASES
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(text))
+                .map(|(_, expr)| *expr)
+                .ok_or_else(|| (0, format!("unknown alias '{}'", text)))?
+        } else {
+            text
+        };
+
+        let fields = words(expr);
+        if fields.len() != 5 {
+            return Err((0, format!("expected 5 fields, found {}", fields.len())));
+        }
+        let mut sets = [0; 5];
+        for (i, (offset, field)) in fields.iter().enumerate() {
+            sets[i] = parse_field(field, FIELDS[i]).map_err(|(o, what)| (offset + o, what))?;
+        }
+        if sets[4] & 1 << 7 != 0 {
+            sets[4] = (sets[4] | 1) & !(1 << 7);
+        }
+
+        Ok(Cron {
+            text: text.to_string(),
+            minutes: sets[0],
+            hours: sets[1],
+            days: sets[2],
+            months: sets[3],
+            weekdays: sets[4],
+            any_day: fields[2].1.starts_with('*'),
+            any_weekday: fields[4].1.starts_with('*'),
+        })
+    }
+}
+
+impl TryFrom<String> for Cron {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse().map_err(|(_, what)| what)
+    }
+}
+
+impl From<Cron> for String {
+    fn from(cron: Cron) -> Self {
+        cron.text
+    }
+}
+
+impl Display for Cron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// a comma separated list of *, values and ranges, each with an optional /step
+fn parse_field(
+    field: &str,
+    (name, min, max, names): (&str, u32, u32, &[&str]),
+) -> Result<u64, (usize, String)> {
+    let mut set = 0;
+    let mut offset = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => {
+                    let at = offset + range.len() + 1;
+                    return Err((at, format!("invalid step '{}' in {}", step, name)));
+                }
+            },
+            None => (part, None),
+        };
+
+        let value = |text: &str, at: usize| -> Result<u32, (usize, String)> {
+            let value = match text.parse::<u32>() {
+                Ok(value) => value,
+                Err(_) => names
+                    .iter()
+                    .position(|n| n.eq_ignore_ascii_case(text))
+                    .map(|i| min + i as u32)
+                    .ok_or_else(|| (at, format!("invalid {} '{}'", name, text)))?,
+            };
+            if value < min || value > max {
+                return Err((
+                    at,
+                    format!("{} {} is not between {}-{}", name, value, min, max),
+                ));
+            }
+            Ok(value)
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start, offset)?, value(end, offset + start.len() + 1)?),
+            None => {
+                let start = value(range, offset)?;
+                (start, if step.is_some() { max } else { start })
+            }
+        };
+        if start > end {
+            return Err((offset, format!("{} range '{}' goes backwards", name, range)));
+        }
+
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            set |= 1 << value;
+        }
+        offset += part.len() + 1;
+    }
+    Ok(set)
+}
+
+fn bits(set: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |i| set & 1 << i != 0)
+}
+
+// every field along with its byte offset
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    words
+}
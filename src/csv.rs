@@ -0,0 +1,149 @@
+use crate::{
+    lexer::{Lexer, Loc},
+    parser::{self, Parsed, ParserError},
+    Reminder,
+};
+
+// csv with the columns summary,body,trigger,tags. the trigger is written the same way as in
+// a remind statement and tags are separated by spaces. a header row can put the columns in
+// any order, without one they are in that order
+
+pub const COLUMNS: [&str; 4] = ["summary", "body", "trigger", "tags"];
+
+pub fn import(text: &str, file_path: Option<String>) -> Parsed {
+    let mut parsed = Parsed::default();
+    let mut records = records(text).peekable();
+
+    // where each of COLUMNS is
+    let mut columns = [Some(0), Some(1), Some(2), Some(3)];
+    if let Some((_, header)) = records.peek() {
+        if header
+            .iter()
+            .any(|h| h.trim().eq_ignore_ascii_case("summary"))
+        {
+            for (i, name) in COLUMNS.iter().enumerate() {
+                columns[i] = header
+                    .iter()
+                    .position(|h| h.trim().eq_ignore_ascii_case(name));
+            }
+            records.next();
+        }
+    }
+
+    for (lnum, record) in records {
+        let loc = Loc {
+            file_path: file_path.clone(),
+            row: lnum + 1,
+            col: 1,
+        };
+        let field = |i: usize| {
+            columns[i]
+                .and_then(|c| record.get(c))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+
+        let trigger = match field(2) {
+            Some(trigger) => trigger,
+            None => {
+                parsed.errors.push(ParserError::Unsupported(
+                    loc,
+                    String::from("row without a trigger"),
+                ));
+                continue;
+            }
+        };
+        let mut lexer = Lexer::new_at_line(trigger.chars(), file_path.clone(), lnum);
+        let when = match parser::parse_when(&mut lexer).and_then(|when| {
+            parser::expect_end(&mut lexer)?;
+            Ok(when)
+        }) {
+            Ok(when) => when,
+            Err(e) => {
+                parsed.errors.push(e);
+                continue;
+            }
+        };
+
+        let summary = field(0).unwrap_or("no summary provided").to_string();
+        let mut reminder = Reminder::new(when, summary, field(1).map(String::from));
+        reminder.set_tags(
+            field(3)
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(String::from)
+                .collect(),
+        );
+        parsed.reminders.push(reminder);
+    }
+    parsed
+}
+
+pub fn export(reminders: &[Reminder]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for r in reminders {
+        let fields = [
+            r.summary().to_string(),
+            r.body().unwrap_or_default().to_string(),
+            r.when().to_string(),
+            r.tags().join(" "),
+        ];
+        let fields: Vec<String> = fields.iter().map(|f| quote(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn quote(field: &str) -> String {
+    if field.contains(['"', ',', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+// RFC 4180 records along with the zero based line they start on,
+// quoted fields can hold commas, newlines and "" for a quote
+fn records(text: &str) -> impl Iterator<Item = (usize, Vec<String>)> + '_ {
+    let mut chars = text.chars().peekable();
+    let mut lnum = 0;
+    std::iter::from_fn(move || {
+        // blank lines dont hold a record
+        while chars.peek() == Some(&'\n') || chars.peek() == Some(&'\r') {
+            if chars.next() == Some('\n') {
+                lnum += 1;
+            }
+        }
+        chars.peek()?;
+
+        let start = lnum;
+        let mut record = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = !quoted,
+                ',' if !quoted => record.push(std::mem::take(&mut field)),
+                '\r' if !quoted => {}
+                '\n' if !quoted => {
+                    lnum += 1;
+                    break;
+                }
+                c => {
+                    if c == '\n' {
+                        lnum += 1;
+                    }
+                    field.push(c);
+                }
+            }
+        }
+        record.push(field);
+        Some((start, record))
+    })
+}
@@ -0,0 +1,291 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+
+use crate::{
+    control,
+    lexer::{Lexer, Loc, Token, TokenKind},
+    parser::{self, ParserError},
+    reminder::Priority,
+    Reminder,
+};
+
+// which reminders list, agenda, export, rm and snooze cover, eg:
+//   tag:work and due<7d and not done
+//   summary~"deploy" or (priority>=high and state:snoozed)
+// the terms are
+//   tag:<tag>            has the tag, +work, @oncall or just work for either
+//   due<7d               next goes off within (< <=) or not until after (> >=) that long from now
+//   priority>=high       also < <= > and priority:high for exactly
+//   summary~<text>       the summary, or body~ the body, has the text in it ignoring case
+//   done active snoozed  its state, also state:done
+// not binds tightest, then and, then or
+
+const STATES: [&str; 3] = ["active", "snoozed", "done"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cmp {
+    Less,
+    LessEq,
+    Equal,
+    GreaterEq,
+    Greater,
+}
+
+impl Cmp {
+    fn test<T: PartialOrd>(self, a: T, b: T) -> bool {
+        match self {
+            Cmp::Less => a < b,
+            Cmp::LessEq => a <= b,
+            Cmp::Equal => a == b,
+            Cmp::GreaterEq => a >= b,
+            Cmp::Greater => a > b,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Tag(String),
+    Due(Cmp, u32), //seconds from now
+    Priority(Cmp, Priority),
+    Summary(String),
+    Body(String),
+    State(String),
+}
+
+impl Filter {
+    pub fn matches(&self, reminder: &Reminder, now: DateTime<Local>) -> bool {
+        let has = |text: &str, part: &str| text.to_lowercase().contains(&part.to_lowercase());
+        match self {
+            Filter::Not(f) => !f.matches(reminder, now),
+            Filter::And(a, b) => a.matches(reminder, now) && b.matches(reminder, now),
+            Filter::Or(a, b) => a.matches(reminder, now) || b.matches(reminder, now),
+            Filter::Tag(tag) => reminder.has_tag(tag),
+            // a reminder that is done or never goes off again is never due
+            Filter::Due(cmp, seconds) => match reminder.due(now) {
+                Some(due) if !reminder.is_done() => {
+                    cmp.test((due - now).num_seconds(), *seconds as i64)
+                }
+                _ => false,
+            },
+            Filter::Priority(cmp, priority) => cmp.test(reminder.priority(), *priority),
+            Filter::Summary(text) => has(reminder.summary(), text),
+            Filter::Body(text) => reminder.body().is_some_and(|body| has(body, text)),
+            Filter::State(state) => control::state(reminder) == state,
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Result<Filter, FilterError> {
+    let mut lexer = Lexer::new(text.chars(), None);
+    let filter = parse_or(&mut lexer)?;
+    let token = lexer.next_token();
+    match token.kind {
+        TokenKind::End => Ok(filter),
+        _ => Err(expected(token, "and, or or the end")),
+    }
+}
+
+fn parse_or<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Filter, FilterError> {
+    let mut filter = parse_and(lexer)?;
+    while is(lexer.peek_token(), "or") {
+        lexer.next_token();
+        filter = Filter::Or(Box::new(filter), Box::new(parse_and(lexer)?));
+    }
+    Ok(filter)
+}
+
+fn parse_and<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Filter, FilterError> {
+    let mut filter = parse_not(lexer)?;
+    while is(lexer.peek_token(), "and") {
+        lexer.next_token();
+        filter = Filter::And(Box::new(filter), Box::new(parse_not(lexer)?));
+    }
+    Ok(filter)
+}
+
+fn parse_not<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Filter, FilterError> {
+    if is(lexer.peek_token(), "not") {
+        lexer.next_token();
+        return Ok(Filter::Not(Box::new(parse_not(lexer)?)));
+    }
+    parse_term(lexer)
+}
+
+fn parse_term<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Filter, FilterError> {
+    let token = lexer.next_token();
+    if token.kind == TokenKind::OpenParen {
+        let filter = parse_or(lexer)?;
+        let close = lexer.next_token();
+        if close.kind != TokenKind::CloseParen {
+            return Err(expected(close, "a closing )"));
+        }
+        return Ok(filter);
+    }
+    if !is_word(&token) {
+        return Err(expected(token, "a term like tag:work, due<7d or done"));
+    }
+    let field = token.text.to_lowercase();
+    match field.as_str() {
+        state if STATES.contains(&state) => Ok(Filter::State(field)),
+        "state" => {
+            expect(lexer, TokenKind::Colon, ":")?;
+            let value = value(lexer)?;
+            match STATES.iter().find(|s| value.text.eq_ignore_ascii_case(s)) {
+                Some(state) => Ok(Filter::State(state.to_string())),
+                None => Err(invalid(value, "active/snoozed/done")),
+            }
+        }
+        "tag" => {
+            expect(lexer, TokenKind::Colon, ":")?;
+            Ok(Filter::Tag(value(lexer)?.text))
+        }
+        "due" => {
+            let cmp = parse_cmp(lexer)?;
+            if cmp == Cmp::Equal {
+                return Err(FilterError::Expected(
+                    token.loc,
+                    String::from("due:"),
+                    "due followed by < <= > or >=",
+                ));
+            }
+            let seconds = parser::parse_span(lexer).map_err(FilterError::Duration)?;
+            Ok(Filter::Due(cmp, seconds))
+        }
+        "priority" => {
+            let cmp = parse_cmp(lexer)?;
+            let value = value(lexer)?;
+            match value.text.trim_start_matches('!').parse() {
+                Ok(priority) => Ok(Filter::Priority(cmp, priority)),
+                Err(_) => Err(invalid(value, "low/normal/high")),
+            }
+        }
+        "summary" | "body" => {
+            expect(lexer, TokenKind::Tilde, "~")?;
+            let text = value(lexer)?.text;
+            Ok(match field.as_str() {
+                "summary" => Filter::Summary(text),
+                _ => Filter::Body(text),
+            })
+        }
+        _ => Err(FilterError::UnknownField(token.loc, token.text)),
+    }
+}
+
+// : and = are the same, < <= > >=
+fn parse_cmp<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Cmp, FilterError> {
+    let token = lexer.next_token();
+    let or_equal = |lexer: &mut Lexer<C>| {
+        let equal = lexer.peek_token().kind == TokenKind::Equals;
+        if equal {
+            lexer.next_token();
+        }
+        equal
+    };
+    match token.kind {
+        TokenKind::Colon | TokenKind::Equals => Ok(Cmp::Equal),
+        TokenKind::Less if or_equal(lexer) => Ok(Cmp::LessEq),
+        TokenKind::Less => Ok(Cmp::Less),
+        TokenKind::Greater if or_equal(lexer) => Ok(Cmp::GreaterEq),
+        TokenKind::Greater => Ok(Cmp::Greater),
+        _ => Err(expected(token, "one of : = < <= > >=")),
+    }
+}
+
+// a word, a quoted string, a +tag/@tag or a !priority
+fn value<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Token, FilterError> {
+    let token = lexer.next_token();
+    match token.kind {
+        TokenKind::Str | TokenKind::Tag | TokenKind::Priority => Ok(token),
+        _ if is_word(&token) => Ok(token),
+        _ => Err(expected(token, "a value")),
+    }
+}
+
+fn expect<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    kind: TokenKind,
+    what: &'static str,
+) -> Result<Token, FilterError> {
+    lexer
+        .expect_token(kind)
+        .map_err(|token| expected(token, what))
+}
+
+// words like h and list lex as keywords, in a filter they are only words
+fn is_word(token: &Token) -> bool {
+    token.kind != TokenKind::UnclosedStr
+        && !token.text.is_empty()
+        && token.text.chars().all(char::is_alphabetic)
+}
+
+fn is(token: &Token, word: &str) -> bool {
+    is_word(token) && token.text.eq_ignore_ascii_case(word)
+}
+
+fn expected(token: Token, what: &'static str) -> FilterError {
+    FilterError::Expected(token.loc, token.text, what)
+}
+
+fn invalid(token: Token, what: &'static str) -> FilterError {
+    FilterError::InvalidValue(token.loc, token.text, what)
+}
+
+#[derive(Debug, Clone)]
+pub enum FilterError {
+    Expected(Loc, String, &'static str), //found, what was expected
+    UnknownField(Loc, String),
+    InvalidValue(Loc, String, &'static str), //value, what it should be
+    Duration(ParserError),
+}
+
+impl FilterError {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            FilterError::Expected(loc, ..)
+            | FilterError::UnknownField(loc, _)
+            | FilterError::InvalidValue(loc, ..) => loc,
+            FilterError::Duration(e) => e.loc(),
+        }
+    }
+}
+
+impl Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterError::Expected(loc, found, what) if found.is_empty() => {
+                write!(f, "{}: expected {}, found the end", loc, what)
+            }
+            FilterError::Expected(loc, found, what) => {
+                write!(f, "{}: expected {}, found '{}'", loc, what, found)
+            }
+            FilterError::UnknownField(loc, field) => write!(
+                f,
+                "{}: unknown field '{}', expected tag/due/priority/summary/body/state",
+                loc, field
+            ),
+            FilterError::InvalidValue(loc, value, what) => {
+                write!(f, "{}: invalid value '{}', expected {}", loc, value, what)
+            }
+            FilterError::Duration(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FilterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FilterError::Duration(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// the error with the filter under it and a ^ where it went wrong, for the command line
+pub fn diagnose(text: &str, e: &FilterError) -> String {
+    format!("{}\n  {}\n  {}^", e, text, " ".repeat(e.loc().col - 1))
+}
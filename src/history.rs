@@ -0,0 +1,156 @@
+use std::{
+    env,
+    fmt::Display,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use chrono::{DateTime, Local};
+
+// every notification the daemon shows, or tries to, and what the user did with it. appended
+// to $XDG_STATE_HOME/remember/notifications, a line each with the fields split by tabs:
+//   <time> <id|-> <reminder|warning|action> <backend> <ok|error <message>> <action|-> <summary>
+// the id is the store's, - for a reminder from a reminder file. the action is what the quiet
+// hours made of it (silenced, dropped) or what the user did (opened <url>)
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Reminder,
+    Warning,
+    Action, //something the user did with a notification that was shown
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::Reminder => write!(f, "reminder"),
+            Kind::Warning => write!(f, "warning"),
+            Kind::Action => write!(f, "action"),
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "reminder" => Ok(Kind::Reminder),
+            "warning" => Ok(Kind::Warning),
+            "action" => Ok(Kind::Action),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery {
+    pub time: DateTime<Local>,
+    pub id: Option<u32>,
+    pub kind: Kind,
+    pub backend: String,
+    pub result: Result<(), String>,
+    pub action: Option<String>,
+    pub summary: String,
+}
+
+impl Delivery {
+    // its line in the history, also how it is written as tsv
+    pub fn line(&self) -> String {
+        // a tab or newline would split the line
+        let clean = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.time.to_rfc3339(),
+            self.id.map_or(String::from("-"), |id| id.to_string()),
+            self.kind,
+            clean(&self.backend),
+            match &self.result {
+                Ok(()) => String::from("ok"),
+                Err(e) => format!("error {}", clean(e)),
+            },
+            self.action.as_deref().map_or(String::from("-"), clean),
+            clean(&self.summary)
+        )
+    }
+
+    fn parse(line: &str) -> Option<Delivery> {
+        let fields: Vec<&str> = line.splitn(7, '\t').collect();
+        let [time, id, kind, backend, result, action, summary] = fields[..] else {
+            return None;
+        };
+        Some(Delivery {
+            time: DateTime::parse_from_rfc3339(time)
+                .ok()?
+                .with_timezone(&Local),
+            id: match id {
+                "-" => None,
+                id => Some(id.parse().ok()?),
+            },
+            kind: kind.parse().ok()?,
+            backend: backend.to_string(),
+            result: match result {
+                "ok" => Ok(()),
+                result => Err(result.strip_prefix("error ")?.to_string()),
+            },
+            action: match action {
+                "-" => None,
+                action => Some(action.to_string()),
+            },
+            summary: summary.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: PathBuf) -> History {
+        History { path }
+    }
+
+    pub fn record(&self, delivery: &Delivery) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(format!("{}\n", delivery.line()).as_bytes())
+    }
+
+    // oldest first, from `since` on. a line that doesnt parse is skipped, the daemon may have
+    // been stopped part way through one
+    pub fn read(&self, since: Option<DateTime<Local>>) -> io::Result<Vec<Delivery>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(text
+            .lines()
+            .filter_map(Delivery::parse)
+            .filter(|d| since.is_none_or(|since| d.time >= since))
+            .collect())
+    }
+}
+
+// $XDG_STATE_HOME/remember, where the daemon and the shell keep what they write as they run
+pub fn state_dir() -> Option<PathBuf> {
+    let state_home = match env::var_os("XDG_STATE_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".local/state"),
+    };
+    Some(state_home.join("remember"))
+}
+
+// $XDG_STATE_HOME/remember/notifications
+pub fn default_path() -> Option<PathBuf> {
+    Some(state_dir()?.join("notifications"))
+}
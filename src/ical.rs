@@ -0,0 +1,625 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc, Weekday,
+};
+
+use chrono_tz::Tz;
+
+use crate::{
+    cron::Cron,
+    lexer::Loc,
+    parser::{Parsed, ParserError},
+    reminder, Reminder, When,
+};
+
+// iCalendar (RFC 5545), every reminder is a VEVENT with a single VALARM at its start,
+// a one off date that already passed is still exported as itself. a cron expression that
+// goes off on a day matching either day field is two VEVENTs, one for each. a rule that
+// cant be read without changing when it goes off is an error
+
+pub fn export(reminders: &[Reminder], now: DateTime<Local>) -> String {
+    let mut out = String::new();
+    line(&mut out, "BEGIN:VCALENDAR");
+    line(&mut out, "VERSION:2.0");
+    line(&mut out, "PRODID:-//Remember//Remember//EN");
+    for r in reminders {
+        let start = match r.due(now) {
+            Some(start) => start,
+            None => continue,
+        };
+        let mut hasher = DefaultHasher::new();
+        r.key().hash(&mut hasher);
+        let uid = hasher.finish();
+
+        let rules = rrules(r.when());
+        let rules: Vec<Option<&String>> = if rules.is_empty() {
+            vec![None]
+        } else {
+            rules.iter().map(Some).collect()
+        };
+        for (i, rrule) in rules.into_iter().enumerate() {
+            line(&mut out, "BEGIN:VEVENT");
+            match i {
+                0 => line(&mut out, &format!("UID:{:016x}@remember", uid)),
+                i => line(&mut out, &format!("UID:{:016x}-{}@remember", uid, i)),
+            }
+            line(
+                &mut out,
+                &format!(
+                    "DTSTAMP:{}",
+                    now.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+                ),
+            );
+            let dtstart = match r.when().tz() {
+                Some(tz) => format!(
+                    "DTSTART;TZID={}:{}",
+                    tz,
+                    start.with_timezone(&tz).format("%Y%m%dT%H%M%S")
+                ),
+                None => format!("DTSTART:{}", start.format("%Y%m%dT%H%M%S")),
+            };
+            line(&mut out, &dtstart);
+            if let Some(rrule) = rrule {
+                line(&mut out, &format!("RRULE:{}", rrule));
+            }
+            line(&mut out, &format!("SUMMARY:{}", escape(r.summary())));
+            if let Some(body) = r.body() {
+                line(&mut out, &format!("DESCRIPTION:{}", escape(body)));
+            }
+            line(&mut out, "BEGIN:VALARM");
+            line(&mut out, "ACTION:DISPLAY");
+            line(&mut out, "TRIGGER:PT0S");
+            line(&mut out, &format!("DESCRIPTION:{}", escape(r.summary())));
+            line(&mut out, "END:VALARM");
+            for lead in r.warn() {
+                let text = format!("in {}: {}", reminder::lead_text(*lead), r.summary());
+                line(&mut out, "BEGIN:VALARM");
+                line(&mut out, "ACTION:DISPLAY");
+                line(&mut out, &format!("TRIGGER:{}", before(*lead)));
+                line(&mut out, &format!("DESCRIPTION:{}", escape(&text)));
+                line(&mut out, "END:VALARM");
+            }
+            line(&mut out, "END:VEVENT");
+        }
+    }
+    line(&mut out, "END:VCALENDAR");
+    out
+}
+
+// the rules an event needs to repeat like the reminder, none for one that goes off once
+fn rrules(when: &When) -> Vec<String> {
+    match when {
+        When::Duration(_) => vec![],
+        When::Time(..) => vec![String::from("FREQ=DAILY")],
+        When::Day(d, ..) => vec![format!("FREQ=WEEKLY;BYDAY={}", &d[..2].to_uppercase())],
+        When::Date(d, 0, 0, ..) => vec![format!("FREQ=MONTHLY;BYMONTHDAY={}", d)],
+        When::Date(d, 0, y, ..) => vec![format!(
+            "FREQ=MONTHLY;BYMONTHDAY={};UNTIL={}1231T235959",
+            d, y
+        )],
+        When::Date(d, mon, 0, ..) => vec![format!("FREQ=YEARLY;BYMONTH={};BYMONTHDAY={}", mon, d)],
+        When::Date(..) => vec![],
+        When::Zoned(when, _) => rrules(when),
+        // every BY part limits a MINUTELY rule, so a day matching either of the two day
+        // fields takes a rule for each
+        When::Cron(cron) if cron.either_day() => {
+            vec![cron_rule(cron, true, false), cron_rule(cron, false, true)]
+        }
+        When::Cron(cron) => vec![cron_rule(cron, true, true)],
+    }
+}
+
+fn cron_rule(cron: &Cron, days: bool, weekdays: bool) -> String {
+    let mut rule = String::from("FREQ=MINUTELY");
+    let parts = [
+        ("BYMINUTE", cron.minutes(), 60, true),
+        ("BYHOUR", cron.hours(), 24, true),
+        ("BYMONTHDAY", cron.days(), 31, days),
+        ("BYMONTH", cron.months(), 12, true),
+    ];
+    for (name, values, all, kept) in parts {
+        if kept && values.len() < all {
+            let values: Vec<String> = values.iter().map(u32::to_string).collect();
+            rule.push_str(&format!(";{}={}", name, values.join(",")));
+        }
+    }
+    let days = cron.weekdays();
+    if weekdays && days.len() < 7 {
+        let days: Vec<&str> = days.iter().map(|d| BYDAY[*d as usize]).collect();
+        rule.push_str(&format!(";BYDAY={}", days.join(",")));
+    }
+    rule
+}
+
+// a negative DURATION of lead seconds
+fn before(lead: u32) -> String {
+    if lead.is_multiple_of(7 * 86400) {
+        format!("-P{}W", lead / (7 * 86400))
+    } else if lead.is_multiple_of(86400) {
+        format!("-P{}D", lead / 86400)
+    } else if lead.is_multiple_of(3600) {
+        format!("-PT{}H", lead / 3600)
+    } else if lead.is_multiple_of(60) {
+        format!("-PT{}M", lead / 60)
+    } else {
+        format!("-PT{}S", lead)
+    }
+}
+
+const BYDAY: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+// content lines end in CRLF and are folded at 75 octets
+fn line(out: &mut String, text: &str) {
+    let mut len = 0;
+    for c in text.chars() {
+        if len + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            len = 1;
+        }
+        out.push(c);
+        len += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => out.push(' '), //reminders are a single line
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+    loc: Loc,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Default)]
+struct Component {
+    props: Vec<Property>,
+    alarms: Vec<Vec<Property>>,
+}
+
+impl Component {
+    fn get(&self, name: &str) -> Option<&Property> {
+        self.props.iter().find(|p| p.name == name)
+    }
+}
+
+// VEVENTs and VTODOs with a VALARM become a reminder, earlier VALARMs are its lead times.
+// anything else is skipped
+pub fn import(text: &str, file_path: Option<String>) -> Parsed {
+    let mut parsed = Parsed::default();
+    let mut component: Option<(Loc, Component)> = None;
+    let mut alarm: Option<Vec<Property>> = None;
+
+    for prop in unfold(text, &file_path) {
+        match (prop.name.as_str(), prop.value.to_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") | ("BEGIN", "VTODO") => {
+                component = Some((prop.loc, Component::default()))
+            }
+            ("BEGIN", "VALARM") if component.is_some() => alarm = Some(vec![]),
+            ("END", "VALARM") => {
+                if let (Some((_, c)), Some(a)) = (&mut component, alarm.take()) {
+                    c.alarms.push(a);
+                }
+            }
+            ("END", "VEVENT") | ("END", "VTODO") => {
+                if let Some((loc, c)) = component.take() {
+                    for (a, warn) in group(&c.alarms) {
+                        match reminder(&c, a, &loc) {
+                            Ok(mut r) => {
+                                r.iter_mut().for_each(|r| r.set_warn(warn.clone()));
+                                parsed.reminders.append(&mut r)
+                            }
+                            Err(e) => parsed.errors.push(e),
+                        }
+                    }
+                }
+            }
+            _ => match (&mut alarm, &mut component) {
+                (Some(a), _) => a.push(prop),
+                (None, Some((_, c))) => c.props.push(prop),
+                _ => {}
+            },
+        }
+    }
+    parsed
+}
+
+// the alarms that become reminders along with their lead times. relative alarms from the
+// same end of the component are lead times of the last of them
+fn group(alarms: &[Vec<Property>]) -> Vec<(&[Property], Vec<u32>)> {
+    let mut groups: Vec<(&[Property], Vec<u32>)> = vec![];
+    let mut relative = vec![];
+    for a in alarms {
+        let offset = a
+            .iter()
+            .find(|p| p.name == "TRIGGER" && p.param("VALUE") != Some("DATE-TIME"))
+            .and_then(|t| Some((t.param("RELATED") == Some("END"), duration(t).ok()?)));
+        match offset {
+            Some((end, offset)) => relative.push((end, offset, a.as_slice())),
+            None => groups.push((a, vec![])),
+        }
+    }
+
+    for end in [false, true] {
+        let group: Vec<_> = relative.iter().filter(|(e, ..)| *e == end).collect();
+        if let Some((_, last, a)) = group.iter().max_by_key(|(_, offset, _)| *offset) {
+            let warn = group
+                .iter()
+                .map(|(_, offset, _)| (*last - *offset).num_seconds())
+                .filter(|lead| *lead > 0)
+                .map(|lead| lead as u32)
+                .collect();
+            groups.push((a, warn));
+        }
+    }
+    groups
+}
+
+fn unfold(text: &str, file_path: &Option<String>) -> Vec<Property> {
+    let mut lines: Vec<(usize, String)> = vec![];
+    for (lnum, l) in text.lines().enumerate() {
+        match (
+            l.strip_prefix(' ').or_else(|| l.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(rest), Some((_, last))) => last.push_str(rest),
+            _ => lines.push((lnum, l.to_string())),
+        }
+    }
+
+    lines
+        .into_iter()
+        .filter_map(|(lnum, l)| {
+            let (head, value) = l.split_once(':')?;
+            let mut head = head.split(';');
+            let name = head.next()?.to_uppercase();
+            let params = head
+                .filter_map(|p| p.split_once('='))
+                .map(|(k, v)| (k.to_uppercase(), v.trim_matches('"').to_string()))
+                .collect();
+            Some(Property {
+                name,
+                params,
+                value: value.to_string(),
+                loc: Loc {
+                    file_path: file_path.clone(),
+                    row: lnum + 1,
+                    col: 1,
+                },
+            })
+        })
+        .collect()
+}
+
+fn reminder(c: &Component, alarm: &[Property], loc: &Loc) -> Result<Vec<Reminder>, ParserError> {
+    let unsupported =
+        |loc: &Loc, what: &str| ParserError::Unsupported(loc.clone(), what.to_string());
+
+    let summary = c
+        .get("SUMMARY")
+        .or_else(|| alarm.iter().find(|p| p.name == "DESCRIPTION"))
+        .map(|p| unescape(&p.value))
+        .unwrap_or_else(|| String::from("no summary provided"));
+    let body = c.get("DESCRIPTION").map(|p| unescape(&p.value));
+
+    let trigger = alarm
+        .iter()
+        .find(|p| p.name == "TRIGGER")
+        .ok_or_else(|| unsupported(loc, "VALARM without a TRIGGER"))?;
+
+    // when the alarm goes off the first time, the start it is relative to and the zone both are in
+    let (fire, start, tz) = if trigger.param("VALUE") == Some("DATE-TIME") {
+        let fire = date_time(trigger)?;
+        (fire, fire, tz(trigger))
+    } else {
+        let related = match trigger.param("RELATED") {
+            Some("END") => c.get("DTEND").or_else(|| c.get("DUE")),
+            _ => c.get("DTSTART").or_else(|| c.get("DUE")),
+        }
+        .ok_or_else(|| unsupported(&trigger.loc, "relative TRIGGER without a DTSTART or DUE"))?;
+        let start = date_time(related)?;
+        (start + duration(trigger)?, start, tz(related))
+    };
+    let zoned = |when: When| match tz {
+        Some(tz) => When::Zoned(Box::new(when), tz),
+        None => when,
+    };
+
+    let rrule = match c.get("RRULE") {
+        Some(rrule) => rrule,
+        None => {
+            return Ok(vec![Reminder::new(
+                zoned(When::Date(
+                    fire.day(),
+                    fire.month(),
+                    fire.year(),
+                    fire.hour(),
+                    fire.minute(),
+                )),
+                summary,
+                body,
+            )])
+        }
+    };
+
+    let invalid = |part: &str| unsupported(&rrule.loc, &format!("RRULE part {}", part));
+    let numbers = |part: &str, values: &str, min: u32, max: u32| -> Result<Vec<u32>, ParserError> {
+        values
+            .split(',')
+            .map(|v| v.parse().ok().filter(|v| (min..=max).contains(v)))
+            .collect::<Option<Vec<u32>>>()
+            .ok_or_else(|| invalid(part))
+    };
+    let mut freq = None;
+    let mut parts = vec![];
+    let mut until = None;
+    let mut by_day = vec![];
+    let mut by_month = vec![];
+    let mut by_month_day = vec![];
+    let mut by_hour = vec![];
+    let mut by_minute = vec![];
+    for part in rrule.value.split(';') {
+        let Some((name, value)) = part.split_once('=') else {
+            return Err(invalid(part));
+        };
+        match name {
+            "FREQ" => freq = Some(value),
+            "INTERVAL" if value == "1" => {}
+            "UNTIL" => {
+                let date = value.get(..8).unwrap_or_default();
+                until = Some(NaiveDate::parse_from_str(date, "%Y%m%d").map_err(|_| invalid(part))?);
+            }
+            "BYDAY" => {
+                by_day = value
+                    .split(',')
+                    .map(|d| {
+                        weekday(d).ok_or_else(|| unsupported(&rrule.loc, &format!("BYDAY {}", d)))
+                    })
+                    .collect::<Result<_, _>>()?
+            }
+            "BYMONTH" => by_month = numbers(part, value, 1, 12)?,
+            "BYMONTHDAY" => by_month_day = numbers(part, value, 1, 31)?,
+            "BYHOUR" => by_hour = numbers(part, value, 0, 23)?,
+            "BYMINUTE" => by_minute = numbers(part, value, 0, 59)?,
+            _ => return Err(invalid(part)),
+        }
+        if !matches!(name, "FREQ" | "INTERVAL") {
+            parts.push((name, part));
+        }
+    }
+
+    // the parts each FREQ can be read with, any other would change when it goes off
+    let allowed: &[&str] = match freq {
+        Some("DAILY") => &[],
+        Some("WEEKLY") => &["BYDAY"],
+        Some("MONTHLY") => &["BYMONTHDAY", "UNTIL"],
+        Some("YEARLY") => &["BYMONTH", "BYMONTHDAY"],
+        Some("MINUTELY") => &["BYMINUTE", "BYHOUR", "BYMONTHDAY", "BYMONTH", "BYDAY"],
+        _ => return Err(unsupported(&rrule.loc, "RRULE FREQ")),
+    };
+    if let Some((_, part)) = parts.iter().find(|(name, _)| !allowed.contains(name)) {
+        return Err(unsupported(
+            &rrule.loc,
+            &format!("RRULE part {} with FREQ={}", part, freq.unwrap_or_default()),
+        ));
+    }
+
+    // every occurrence the rule has in a month or a year, the alarm keeps its distance from it
+    let offset = fire - start;
+    let on = |date: Option<NaiveDate>, what: String| {
+        date.map(|date| date.and_time(start.time()) + offset)
+            .ok_or_else(|| unsupported(&rrule.loc, &what))
+    };
+    let mut whens = vec![];
+    match freq {
+        Some("DAILY") => whens.push(When::Time(fire.hour(), fire.minute())),
+        Some("WEEKLY") if by_day.is_empty() => whens.push(on_day(fire)),
+        Some("WEEKLY") => {
+            for day in by_day {
+                let shift = (7 + day.num_days_from_monday() as i64
+                    - start.weekday().num_days_from_monday() as i64)
+                    % 7;
+                whens.push(on_day(fire + Duration::days(shift)));
+            }
+        }
+        Some("MONTHLY") => {
+            // a month with every day in it when the start's doesnt have this one
+            let fires = if by_month_day.is_empty() {
+                vec![fire]
+            } else {
+                by_month_day
+                    .iter()
+                    .map(|d| {
+                        let date = NaiveDate::from_ymd_opt(start.year(), start.month(), *d)
+                            .or(NaiveDate::from_ymd_opt(2000, 1, *d));
+                        on(date, format!("BYMONTHDAY {}", d))
+                    })
+                    .collect::<Result<_, _>>()?
+            };
+            for fire in fires {
+                // a date in any month of a year is every month until the year is out
+                let year = match until {
+                    None => 0,
+                    Some(until)
+                        if until.year() == start.year()
+                            && until.month() == 12
+                            && until.day() >= fire.day() =>
+                    {
+                        until.year()
+                    }
+                    Some(until) => {
+                        return Err(unsupported(&rrule.loc, &format!("RRULE UNTIL {}", until)))
+                    }
+                };
+                whens.push(When::Date(fire.day(), 0, year, fire.hour(), fire.minute()));
+            }
+        }
+        Some("YEARLY") => {
+            let months = if by_month.is_empty() {
+                vec![start.month()]
+            } else {
+                by_month
+            };
+            let days = if by_month_day.is_empty() {
+                vec![start.day()]
+            } else {
+                by_month_day
+            };
+            for month in months {
+                for day in &days {
+                    // 2000 was a leap year, so the 29th of february is there
+                    let date = NaiveDate::from_ymd_opt(2000, month, *day);
+                    let fire = on(date, format!("BYMONTHDAY {} in BYMONTH {}", day, month))?;
+                    whens.push(When::Date(
+                        fire.day(),
+                        fire.month(),
+                        0,
+                        fire.hour(),
+                        fire.minute(),
+                    ));
+                }
+            }
+        }
+        _ => {
+            // a cron expression, which cant be moved by the alarm or be in another zone.
+            // one with both day fields would go off on a day matching either
+            if offset != Duration::zero()
+                || tz.is_some()
+                || (!by_month_day.is_empty() && !by_day.is_empty())
+            {
+                return Err(unsupported(&rrule.loc, &format!("RRULE {}", rrule.value)));
+            }
+            let field = |values: Vec<u32>| {
+                let values: Vec<String> = values.iter().map(u32::to_string).collect();
+                if values.is_empty() {
+                    String::from("*")
+                } else {
+                    values.join(",")
+                }
+            };
+            let weekdays = by_day.iter().map(|d| d.num_days_from_sunday()).collect();
+            let expr = [by_minute, by_hour, by_month_day, by_month, weekdays]
+                .map(field)
+                .join(" ");
+            let cron = expr
+                .parse()
+                .map_err(|(_, e): (usize, String)| unsupported(&rrule.loc, &e))?;
+            whens.push(When::Cron(cron));
+        }
+    }
+    Ok(whens
+        .into_iter()
+        .map(|when| Reminder::new(zoned(when), summary.clone(), body.clone()))
+        .collect())
+}
+
+fn on_day(fire: NaiveDateTime) -> When {
+    When::Day(
+        fire.weekday().to_string().to_lowercase(),
+        fire.hour(),
+        fire.minute(),
+    )
+}
+
+fn weekday(day: &str) -> Option<Weekday> {
+    match day {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+// the IANA zone a DATE-TIME is in, other TZIDs are read as local time
+fn tz(prop: &Property) -> Option<Tz> {
+    if prop.value.trim().ends_with('Z') {
+        return None;
+    }
+    prop.param("TZID")?.parse().ok()
+}
+
+// DATE-TIME or DATE as it is written, times in UTC are moved to local time
+fn date_time(prop: &Property) -> Result<NaiveDateTime, ParserError> {
+    let value = prop.value.trim();
+    let parsed = match value.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .map(|t| Local.from_utc_datetime(&t).naive_local()),
+        None => NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y%m%d").map(|d| d.and_hms_opt(0, 0, 0).unwrap())
+        }),
+    };
+    parsed
+        .map_err(|_| ParserError::Unsupported(prop.loc.clone(), format!("{} {}", prop.name, value)))
+}
+
+// [+-]P[nW][nD][T[nH][nM][nS]]
+fn duration(prop: &Property) -> Result<Duration, ParserError> {
+    let invalid = || ParserError::Unsupported(prop.loc.clone(), format!("duration {}", prop.value));
+    let value = prop.value.trim();
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P').ok_or_else(invalid)?;
+
+    let mut total = Duration::zero();
+    let mut num = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => num.push(c),
+            'T' => {}
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let n = num.parse::<i64>().map_err(|_| invalid())?;
+                num.clear();
+                total += match c {
+                    'W' => Duration::weeks(n),
+                    'D' => Duration::days(n),
+                    'H' => Duration::hours(n),
+                    'M' => Duration::minutes(n),
+                    _ => Duration::seconds(n),
+                };
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(total * sign)
+}
@@ -40,10 +40,32 @@ pub enum TokenKind {
     List,
     Edit,
     Help,
+    Include,
+    Let,
+    Cron,
+    Tz,
+    Warn,
+    Quiet,
+    Urgent,
+    Urgency,
+    Icon,
+    Timeout,
+    Category,
+    From,
+    Tag,      //eg: +project or @context
+    Priority, //eg: !high
 
     //sybols
     Colon,
     Semicolon,
+    Slash,
+    Equals,
+    Comma,
+    Less,
+    Greater,
+    Tilde,
+    OpenParen,
+    CloseParen,
 
     //Terminators
     UnclosedStr,
@@ -76,12 +98,17 @@ pub struct Lexer<Chars: Iterator<Item = char>> {
 
 impl<Chars: Iterator<Item = char>> Lexer<Chars> {
     pub fn new(chars: Chars, file_path: Option<String>) -> Self {
+        Self::new_at_line(chars, file_path, 0)
+    }
+
+    // for lexing a single line out of a larger file, lnum is zero based
+    pub fn new_at_line(chars: Chars, file_path: Option<String>, lnum: usize) -> Self {
         Self {
             chars: chars.peekable(),
             exhausted: false,
             peeked: None,
             file_path,
-            lnum: 0,
+            lnum,
             cnum: 0,
             bol: 0,
         }
@@ -90,7 +117,7 @@ impl<Chars: Iterator<Item = char>> Lexer<Chars> {
     pub fn loc(&self) -> Loc {
         Loc {
             file_path: self.file_path.clone(),
-            row: self.lnum,
+            row: self.lnum + 1,
             col: self.cnum - self.bol + 1,
         }
     }
@@ -109,7 +136,7 @@ impl<Chars: Iterator<Item = char>> Lexer<Chars> {
         self.peeked.get_or_insert(token)
     }
 
-    fn next_token(&mut self) -> Token {
+    pub fn next_token(&mut self) -> Token {
         self.peeked.take().unwrap_or_else(|| self.chop_tokens())
     }
 
@@ -134,6 +161,39 @@ impl<Chars: Iterator<Item = char>> Lexer<Chars> {
         }
     }
 
+    // the rest of a string opened with `quote`. a backslash takes the next character as it is
+    // when that is the quote or another backslash, before anything else it is kept
+    fn string(&mut self, quote: char, loc: Loc) -> Token {
+        let mut text = String::new();
+        while let Some(x) = self.chars.next_if(|x| *x != quote) {
+            self.cnum += 1;
+            match self
+                .chars
+                .next_if(|c| x == '\\' && (*c == quote || *c == '\\' || *c == 'n'))
+            {
+                Some('n') => {
+                    self.cnum += 1;
+                    text.push('\n');
+                }
+                Some(c) => {
+                    self.cnum += 1;
+                    text.push(c);
+                }
+                None => text.push(x),
+            }
+        }
+        Token {
+            kind: if self.chars.next_if(|x| *x == quote).is_some() {
+                self.cnum += 1;
+                TokenKind::Str
+            } else {
+                TokenKind::UnclosedStr
+            },
+            text,
+            loc,
+        }
+    }
+
     fn chop_tokens(&mut self) -> Token {
         assert!(
             !self.exhausted,
@@ -158,33 +218,70 @@ impl<Chars: Iterator<Item = char>> Lexer<Chars> {
                 self.cnum += 1;
                 let mut text = x.to_string();
                 match x {
-                    '"' => {
-                        text.clear();
-                        while let Some(x) = self.chars.next_if(|x| *x != '"') {
+                    ':' => Token {
+                        kind: TokenKind::Colon,
+                        text,
+                        loc,
+                    },
+                    ';' => Token {
+                        kind: TokenKind::Semicolon,
+                        text,
+                        loc,
+                    },
+                    '/' => Token {
+                        kind: TokenKind::Slash,
+                        text,
+                        loc,
+                    },
+                    '=' => Token {
+                        kind: TokenKind::Equals,
+                        text,
+                        loc,
+                    },
+                    ',' => Token {
+                        kind: TokenKind::Comma,
+                        text,
+                        loc,
+                    },
+                    '<' => Token {
+                        kind: TokenKind::Less,
+                        text,
+                        loc,
+                    },
+                    '>' => Token {
+                        kind: TokenKind::Greater,
+                        text,
+                        loc,
+                    },
+                    '~' => Token {
+                        kind: TokenKind::Tilde,
+                        text,
+                        loc,
+                    },
+                    '(' => Token {
+                        kind: TokenKind::OpenParen,
+                        text,
+                        loc,
+                    },
+                    ')' => Token {
+                        kind: TokenKind::CloseParen,
+                        text,
+                        loc,
+                    },
+                    '"' | '\'' => self.string(x, loc),
+                    '+' | '@' | '!' if self.chars.peek().is_some_and(|c| c.is_alphanumeric()) => {
+                        while let Some(c) = self
+                            .chars
+                            .next_if(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'))
+                        {
                             self.cnum += 1;
-                            text.push(x);
+                            text.push(c);
                         }
                         Token {
-                            kind: if self.chars.next_if(|x| *x == '"').is_some() {
-                                TokenKind::Str
+                            kind: if x == '!' {
+                                TokenKind::Priority
                             } else {
-                                TokenKind::UnclosedStr
-                            },
-                            text,
-                            loc,
-                        }
-                    }
-                    '\'' => {
-                        text.clear();
-                        while let Some(x) = self.chars.next_if(|x| *x != '\'') {
-                            self.cnum += 1;
-                            text.push(x);
-                        }
-                        Token {
-                            kind: if self.chars.next_if(|x| *x == '\'').is_some() {
-                                TokenKind::Str
-                            } else {
-                                TokenKind::UnclosedStr
+                                TokenKind::Tag
                             },
                             text,
                             loc,
@@ -222,6 +319,19 @@ impl<Chars: Iterator<Item = char>> Lexer<Chars> {
                                     "help" => TokenKind::Help,
                                     "h" => TokenKind::Help,
 
+                                    "include" => TokenKind::Include,
+                                    "let" => TokenKind::Let,
+                                    "cron" => TokenKind::Cron,
+                                    "tz" => TokenKind::Tz,
+                                    "warn" => TokenKind::Warn,
+                                    "quiet" => TokenKind::Quiet,
+                                    "urgent" => TokenKind::Urgent,
+                                    "urgency" => TokenKind::Urgency,
+                                    "icon" => TokenKind::Icon,
+                                    "timeout" => TokenKind::Timeout,
+                                    "category" => TokenKind::Category,
+                                    "from" => TokenKind::From,
+
                                     _ => TokenKind::Str,
                                 },
                                 text,
@@ -1,6 +1,25 @@
 pub mod reminder;
+pub mod cron;
+pub mod ical;
+pub mod csv;
+pub mod todotxt;
+pub mod remind;
 pub mod lexer;
 pub mod parser;
+pub mod quiet;
+pub mod notify;
+pub mod history;
+pub mod links;
+pub mod agenda;
+pub mod output;
+pub mod filter;
+pub mod template;
+pub mod watch;
+pub mod store;
+pub mod control;
+pub mod shell;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 
 pub use reminder::When;
@@ -0,0 +1,90 @@
+use std::{
+    process::{Command, Stdio},
+    thread,
+};
+
+// links in reminder text, eg: "notes at <https://example.com/notes>" or "see https://example.com."
+// a link starts at a known scheme and runs to the next space, quote or angle bracket.
+// punctuation that ends a sentence, and a ) that wasnt opened in the link, isnt part of it
+
+const SCHEMES: [&str; 5] = ["https://", "http://", "ftp://", "file://", "mailto:"];
+
+// byte ranges of every link in text
+pub fn spans(text: &str) -> Vec<(usize, usize)> {
+    let mut spans = vec![];
+    let mut i = 0;
+    while i < text.len() {
+        let rest = &text[i..];
+        let starts_word = text[..i]
+            .chars()
+            .next_back()
+            .is_none_or(|c| c.is_whitespace() || "<([\"'".contains(c));
+        let scheme = SCHEMES.iter().find(|s| {
+            rest.get(..s.len())
+                .is_some_and(|r| r.eq_ignore_ascii_case(s))
+        });
+        match scheme {
+            Some(scheme) if starts_word => {
+                let len = link_len(rest);
+                if len > scheme.len() {
+                    spans.push((i, i + len));
+                }
+                i += len.max(scheme.len());
+            }
+            _ => i += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    spans
+}
+
+fn link_len(text: &str) -> usize {
+    let mut len = text
+        .find(|c: char| c.is_whitespace() || "<>\"'`".contains(c))
+        .unwrap_or(text.len());
+    loop {
+        let link = &text[..len];
+        match link.chars().next_back() {
+            Some('.' | ',' | ';' | ':' | '!' | '?') => len -= 1,
+            Some(')') if link.matches(')').count() > link.matches('(').count() => len -= 1,
+            Some(']') if link.matches(']').count() > link.matches('[').count() => len -= 1,
+            _ => return len,
+        }
+    }
+}
+
+pub fn find(text: &str) -> Vec<&str> {
+    spans(text)
+        .into_iter()
+        .map(|(start, end)| &text[start..end])
+        .collect()
+}
+
+// text with every link made clickable in terminals that understand OSC 8
+pub fn hyperlink(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end) in spans(text) {
+        let link = &text[start..end];
+        out.push_str(&text[last..start]);
+        out.push_str(&format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", link, link));
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+// runs the opener with the link as its last argument, eg: "xdg-open" or "firefox --new-tab"
+pub fn open(opener: &str, link: &str) -> Result<(), String> {
+    let mut words = opener.split_whitespace();
+    let program = words.next().ok_or("no opener command given")?;
+    Command::new(program)
+        .args(words)
+        .arg(link)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map(|mut child| {
+            thread::spawn(move || child.wait());
+        })
+        .map_err(|e| format!("could not run {}: {}", program, e))
+}
@@ -1,246 +1,1011 @@
 use std::{
-    env,
-    io::{self, Write},
+    env, fs,
+    io::{self, IsTerminal},
+    path::PathBuf,
     thread, time, vec,
 };
 
-use chrono::Local;
+use chrono::{DateTime, Datelike, Local};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rustyline::{error::ReadlineError, history::FileHistory, Editor};
 
 use remember::{
-    parser::{Command, ParserError},
+    filter::Filter,
+    history::History,
+    parser::{Command, Parsed},
+    reminder::Priority,
+    store::Store,
+    watch::Watcher,
     *,
 };
 
-use lexer::Lexer;
+use lexer::{Lexer, TokenKind};
+use notify::Backend;
 
+#[derive(Parser)]
+#[command(version, about = "reminds you of things")]
+struct Config {
+    #[arg(
+        short,
+        long = "file",
+        global = true,
+        value_name = "PATH",
+        help = "reminder file or directory to load instead of the config dir, can be repeated"
+    )]
+    files: Vec<PathBuf>,
+    #[command(subcommand)]
+    mode: Option<Mode>,
+}
+
+#[derive(Subcommand)]
 enum Mode {
-    Normal,
-    Deamon,
-    DebugNew,
-    DebugParser,
-    DebugLexer,
+    #[command(about = "add a reminder to the store, eg: add mon 9:00 \"plan the week\"")]
+    Add {
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true)]
+        statement: Vec<String>,
+    },
+    #[command(about = "list every reminder by when it is next due")]
+    List {
+        #[arg(long, default_value = "plain", help = "plain, json or tsv")]
+        format: output::Format,
+        #[command(flatten)]
+        select: Select,
+    },
+    #[command(about = "replace a reminder in the store")]
+    Edit {
+        id: u32,
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true)]
+        statement: Vec<String>,
+    },
+    #[command(about = "remove a reminder from the store")]
+    Rm {
+        #[arg(required_unless_present = "filter")]
+        ids: Vec<u32>,
+        #[arg(long, value_parser = filter_text, conflicts_with = "ids", help = "remove every reminder in the store it matches, see --help")]
+        filter: Option<String>,
+    },
+    #[command(about = "mark a reminder in the store done")]
+    Done { id: u32 },
+    #[command(about = "put a reminder off, for 10m unless told otherwise")]
+    Snooze {
+        #[arg(required_unless_present = "filter")]
+        id: Option<u32>,
+        duration: Option<String>,
+        #[arg(long = "for", value_name = "DURATION", conflicts_with = "duration")]
+        span: Option<String>,
+        #[arg(long, value_parser = filter_text, conflicts_with = "id", help = "snooze every reminder in the store it matches")]
+        filter: Option<String>,
+    },
+    #[command(about = "undo the last change to the store, from any client")]
+    Undo,
+    #[command(about = "make the last undone change again")]
+    Redo,
+    #[command(about = "when reminders in the store were created, edited, fired and so on")]
+    Events { id: Option<u32> },
+    #[command(about = "the notifications the daemon showed, eg: history --since 8h")]
+    History {
+        #[arg(long, value_parser = since, help = "only the last while, eg: 8h or 2d")]
+        since: Option<u32>,
+        #[arg(long, default_value = "plain", help = "plain, json or tsv")]
+        format: output::Format,
+    },
+    #[command(
+        alias = "deamon",
+        about = "send notifications, reloading files as they change"
+    )]
+    Daemon {
+        #[arg(
+            long,
+            default_value = "xdg-open",
+            help = "command links are opened with"
+        )]
+        opener: String,
+    },
+    #[command(about = "print reminders from a .ics, .csv, todo.txt or .rem file as statements")]
+    Import { file: String },
+    #[command(about = "print the loaded reminders in another format")]
+    Export {
+        #[arg(long, default_value = "ics", value_parser = ["ics", "csv", "todo"])]
+        format: String,
+        #[command(flatten)]
+        select: Select,
+    },
+    #[command(about = "everything that goes off in the next few days")]
+    Agenda {
+        #[arg(long, default_value_t = 7, help = "days the agenda covers")]
+        days: u32,
+        #[arg(long, default_value = "plain", help = "plain, json or tsv")]
+        format: output::Format,
+        #[command(flatten)]
+        select: Select,
+    },
+    #[command(about = "the reminder due soonest, for status bars")]
+    Next {
+        #[arg(long, default_value = "plain", help = "plain, json or tsv")]
+        format: output::Format,
+        #[command(flatten)]
+        select: Select,
+    },
+    #[command(about = "this month with the days that have reminders marked")]
+    Cal,
+    #[command(about = "browse and edit the store")]
+    Tui,
+    #[command(about = "the interactive prompt, the default")]
+    Shell,
+    #[command(about = "poke at the internals")]
+    Debug {
+        #[arg(value_enum)]
+        mode: Debug,
+    },
 }
 
-struct Config {
-    file_path: Option<String>,
-    mode: Mode,
+// which reminders list, agenda and next cover
+#[derive(Args)]
+struct Select {
+    #[arg(
+        long = "tag",
+        value_name = "TAG",
+        help = "only reminders with this tag, +work, @oncall or just work, can be repeated"
+    )]
+    tags: Vec<String>,
+    #[arg(long, value_parser = priority, help = "only reminders of at least this priority")]
+    priority: Option<Priority>,
+    #[arg(
+        long,
+        value_name = "EXPR",
+        value_parser = filter,
+        help = "only reminders it matches, eg: 'tag:work and due<7d and not done'"
+    )]
+    filter: Option<Filter>,
 }
 
-impl Config {
-    fn from_iter(args: &mut impl Iterator<Item = String>) -> Config {
-        args.next().expect("Program name should allways be present");
-        let mut config = Self {
-            file_path: None,
-            mode: Mode::Normal,
-        };
+impl Select {
+    fn matches(&self, reminder: &Reminder, now: DateTime<Local>) -> bool {
+        self.tags.iter().all(|tag| reminder.has_tag(tag))
+            && self.priority.is_none_or(|p| reminder.priority() >= p)
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|f| f.matches(reminder, now))
+    }
+}
 
-        while let Some(arg) = args.next() {
-            match arg.as_str() {
-                "--debug" => {
-                    if let Some(mode) = args.next() {
-                        match mode.as_str() {
-                            "parser" => config.mode = Mode::DebugParser,
-                            "lexer" => config.mode = Mode::DebugLexer,
-                            "new" => config.mode = Mode::DebugNew,
-                            _ => {
-                                eprintln!("ERROR :: unknown value for debug mode");
-                                std::process::exit(1);
-                            }
-                        }
-                    } else {
-                        eprintln!("ERROR :: no value given for debug mode");
-                        std::process::exit(1)
-                    }
-                }
+fn filter(text: &str) -> Result<Filter, String> {
+    filter::parse(text).map_err(|e| filter::diagnose(text, &e))
+}
 
-                x if x == "--deamon" || x == "-d" => config.mode = Mode::Deamon,
+// checked here so mistakes are pointed out, then sent on as it is
+fn filter_text(text: &str) -> Result<String, String> {
+    filter(text).map(|_| text.to_string())
+}
 
-                other => {
-                    if config.file_path.is_none() {
-                        config.file_path = Some(other.to_string());
-                    } else {
-                        eprintln!("ERROR :: file path already provided");
-                        std::process::exit(1);
-                    }
+fn since(text: &str) -> Result<u32, String> {
+    reminder::parse_lead(text).ok_or(String::from("expected a number of s/m/h/d/w eg: 8h"))
+}
+
+fn priority(text: &str) -> Result<Priority, String> {
+    text.trim_start_matches('!')
+        .parse()
+        .map_err(|_| String::from("expected low, normal or high"))
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Debug {
+    Lexer,
+    Parser,
+    New,
+}
+
+const TIME_FORMAT: &str = "%a %d/%m/%Y %H:%M %Z";
+
+fn print_help() {
+    println!("remind <statement>     add a reminder to the store");
+    println!("edit <id> <statement>  replace a reminder");
+    println!("rm <id>|where <filter> remove reminders");
+    println!("done <id>              mark a reminder done");
+    println!("snooze <id> [duration] put a reminder off, 10m by default");
+    println!("undo, redo             take back the last change to the store, or make it again");
+    println!("list [tz]              show the reminders and their ids");
+    println!("help, quit             tab completes, ctrl-d quits too");
+    println!("see remember --help for the command line");
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let config_home = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(config_home.join("remember"))
+}
+
+// the given file or directory, otherwise reminders and conf.d/ in the config dir
+fn config_paths(config: &Config) -> Vec<PathBuf> {
+    if !config.files.is_empty() {
+        return config.files.clone();
+    }
+    match config_dir() {
+        Some(dir) => [dir.join("reminders"), dir.join("conf.d")]
+            .into_iter()
+            .filter(|path| path.exists())
+            .collect(),
+        None => vec![],
+    }
+}
+
+fn load(paths: &[PathBuf]) -> Option<Parsed> {
+    match parser::parse_paths(paths) {
+        Ok(parsed) => {
+            for e in &parsed.errors {
+                eprintln!("ERROR :: {}", e);
+            }
+            Some(parsed)
+        }
+        Err(e) => {
+            eprintln!("ERROR :: could not load reminders :: {}", e);
+            None
+        }
+    }
+}
+
+fn reload(paths: &[PathBuf]) -> Option<Parsed> {
+    match parser::reload(paths) {
+        Ok(Ok(parsed)) => {
+            println!("reloaded {} reminders", parsed.reminders.len());
+            Some(parsed)
+        }
+        Ok(Err(errors)) => {
+            for e in &errors {
+                eprintln!("ERROR :: {}", e);
+            }
+            eprintln!("ERROR :: reload failed, keeping the old reminders");
+            None
+        }
+        Err(e) => {
+            eprintln!("ERROR :: could not load reminders :: {}", e);
+            None
+        }
+    }
+}
+
+fn watch(paths: &[PathBuf], parsed: &Parsed) -> Option<Watcher> {
+    let dirs: Vec<PathBuf> = paths.iter().filter(|p| p.is_dir()).cloned().collect();
+    match Watcher::new(&parsed.files, &dirs) {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            eprintln!("ERROR :: could not watch reminder files :: {}", e);
+            None
+        }
+    }
+}
+
+fn run(parsed: Parsed, paths: &[PathBuf], mut store: Store, opener: &str) {
+    let history = history::default_path().map(History::new);
+    let mut backend: Box<dyn Backend> = match notify::Libnotify::new("Remember", opener) {
+        Ok(mut libnotify) => {
+            if let Some(history) = &history {
+                libnotify.set_history(history.clone());
+            }
+            Box::new(libnotify)
+        }
+        Err(e) => {
+            eprintln!("ERROR :: {}, printing reminders instead", e);
+            Box::new(notify::Print)
+        }
+    };
+
+    // only a daemon with a store on disk takes requests
+    let server = match store.path() {
+        Some(_) => match control::Server::bind(&control::socket_path()) {
+            Ok(server) => Some(server),
+            Err(e) => {
+                eprintln!("ERROR :: could not start the control channel :: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut watcher = if paths.is_empty() {
+        None
+    } else {
+        watch(paths, &parsed)
+    };
+    let mut reminders = parsed.reminders;
+    let mut quiet = parsed.quiet;
+
+    while watcher.is_some()
+        || server.is_some()
+        || reminders.iter().any(|r| !r.is_done())
+        || store.iter().any(|(_, r)| !r.is_done())
+    {
+        thread::sleep(time::Duration::from_secs(1));
+
+        let changed = match &mut watcher {
+            Some(w) => w.changed().unwrap_or_else(|e| {
+                eprintln!("ERROR :: {}", e);
+                false
+            }),
+            None => false,
+        };
+        if changed {
+            if let Some(parsed) = reload(paths) {
+                if let Some(w) = watch(paths, &parsed) {
+                    watcher = Some(w);
                 }
+                reminders = reminder::merge(reminders, parsed.reminders);
+                quiet = parsed.quiet;
+            }
+        }
+        if let Some(server) = &server {
+            if let Err(e) = server.poll(&mut store) {
+                eprintln!("ERROR :: {}", e);
             }
         }
 
-        config
+        let now = Local::now();
+        for r in reminders.iter_mut() {
+            tick(r, None, &mut *backend, &quiet, history.as_ref(), now);
+        }
+        let mut changed = false;
+        for (id, r) in store.iter_mut() {
+            changed |= tick(r, Some(id), &mut *backend, &quiet, history.as_ref(), now);
+        }
+        if changed {
+            if let Err(e) = store.save() {
+                eprintln!("ERROR :: {}", e);
+            }
+        }
     }
 }
 
-fn print_help() {
-    println!("Remember");
-    println!("Desc - reminds you of things");
-    println!("usage - remember [options] [config]");
-    println!("options:");
-    println!("  -d --deamon     start in deamon mode");
-    println!("  --debug <mode>  start in specifide debug mode");
+// shows the reminder or one of its warnings if it is time and records it in the history,
+// returns whether that changed the reminder so the store has to be saved
+fn tick(
+    r: &mut Reminder,
+    id: Option<u32>,
+    backend: &mut dyn Backend,
+    quiet: &quiet::Quiet,
+    history: Option<&History>,
+    now: DateTime<Local>,
+) -> bool {
+    if r.is_done() {
+        return false;
+    }
+    let lead = if r.check(now) {
+        None
+    } else if let Some(lead) = r.check_warn(now) {
+        Some(lead)
+    } else {
+        return false;
+    };
+    let action = quiet.action(r, now);
+    let shown = r
+        .deliver(backend, id, lead, action)
+        .map_err(|e| e.to_string());
+    if let Err(e) = &shown {
+        eprintln!("ERROR :: {}", e);
+    }
+    let action = match action {
+        // it is tried again every second until the quiet hours are over
+        Some(quiet::Action::Defer) => return false,
+        Some(quiet::Action::Drop) => Some(String::from("dropped")),
+        Some(quiet::Action::Silent) => Some(String::from("silenced")),
+        None => None,
+    };
+    // a reminder that failed to show is left as it was
+    let changed = shown.is_ok();
+    let delivery = history::Delivery {
+        time: now,
+        id,
+        kind: match lead {
+            Some(_) => history::Kind::Warning,
+            None => history::Kind::Reminder,
+        },
+        backend: backend.name().to_string(),
+        result: shown,
+        action,
+        summary: r.summary().to_string(),
+    };
+    if let Some(Err(e)) = history.map(|h| h.record(&delivery)) {
+        eprintln!("ERROR :: could not record the history :: {}", e);
+    }
+    changed
+}
+
+// the daemon if one is running, the store otherwise
+fn connect() -> control::Control {
+    let Some(path) = store::default_path() else {
+        eprintln!("ERROR :: no home directory to keep the store in");
+        std::process::exit(1);
+    };
+    match control::Control::connect(control::socket_path(), path) {
+        Ok(control) => control,
+        Err(e) => {
+            eprintln!("ERROR :: could not open the store :: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "tui")]
+fn tui() {
+    if let Err(e) = remember::tui::run(connect()) {
+        eprintln!("ERROR :: {}", e);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn tui() {
+    eprintln!("ERROR :: remember was built without the tui feature");
+    std::process::exit(1);
 }
 
 fn normal(config: Config) {
     println!("starting in normal mode");
+    shell(load(&config_paths(&config)).unwrap_or_default());
 }
 
-fn deamon(config: Config) {
+fn deamon(config: Config, opener: &str) {
     println!("starting in deamon mode");
+    let paths = config_paths(&config);
+    let mut store = open_store().unwrap_or_else(|e| {
+        eprintln!("ERROR :: could not open the store :: {}", e);
+        std::process::exit(1);
+    });
+    // kept until the daemon stops, clients send their requests here meanwhile
+    let _lock = store.lock().unwrap_or_else(|e| {
+        eprintln!("ERROR :: could not lock the store :: {}", e);
+        std::process::exit(1);
+    });
+    run(load(&paths).unwrap_or_default(), &paths, store, opener);
+}
+
+// prints the reminders as statements, ready to be put in a reminder file
+fn import(path: String) {
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("ERROR :: could not read {} :: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    let parsed = match path.rsplit('.').next().map(|ext| ext.to_lowercase()) {
+        Some(ext) if ext == "ics" => ical::import(&text, Some(path)),
+        Some(ext) if ext == "csv" => csv::import(&text, Some(path)),
+        Some(ext) if ext == "txt" => todotxt::import(&text, Some(path)),
+        Some(ext) if ext == "rem" || ext == "reminders" => remind::import(&text, Some(path)),
+        _ => {
+            eprintln!(
+                "ERROR :: dont know how to import {}, expected .ics, .csv, .txt or .rem",
+                path
+            );
+            std::process::exit(1);
+        }
+    };
+    for e in &parsed.errors {
+        eprintln!("ERROR :: {}", e);
+    }
+    for r in &parsed.reminders {
+        println!("{}", r);
+    }
+}
+
+fn export(config: Config, format: &str, select: Select) {
+    let mut parsed = load(&config_paths(&config)).unwrap_or_default();
+    let now = Local::now();
+    parsed.reminders.retain(|r| select.matches(r, now));
+    match format {
+        "csv" => print!("{}", csv::export(&parsed.reminders)),
+        "todo" => print!("{}", todotxt::export(&parsed.reminders, now)),
+        _ => print!("{}", ical::export(&parsed.reminders, now)),
+    }
 }
 
-fn debug_new(config: Config) {
+// the store in the data dir, one in memory when there is no home directory
+fn open_store() -> Result<Store, store::StoreError> {
+    match store::default_path() {
+        Some(path) => Store::open(path),
+        None => Ok(Store::new(None)),
+    }
+}
+
+// the reminders from the reminder files and the store
+fn load_all(config: &Config) -> (Parsed, Store) {
+    let parsed = load(&config_paths(config)).unwrap_or_default();
+    let store = open_store().unwrap_or_else(|e| {
+        eprintln!("ERROR :: could not open the store :: {}", e);
+        Store::new(None)
+    });
+    (parsed, store)
+}
+
+// prints list, agenda or next for every reminder in the reminder files and the store
+fn report(config: Config, mode: Mode) {
+    let (parsed, store) = load_all(&config);
+    let now = Local::now();
+    let entries: Vec<output::Entry> = parsed
+        .reminders
+        .iter()
+        .map(|reminder| output::Entry { id: None, reminder })
+        .chain(store.iter().map(|(id, reminder)| output::Entry {
+            id: Some(id),
+            reminder,
+        }))
+        .filter(|e| match &mode {
+            Mode::List { select, .. } | Mode::Agenda { select, .. } | Mode::Next { select, .. } => {
+                select.matches(e.reminder, now)
+            }
+            _ => true,
+        })
+        .collect();
+    let text = match mode {
+        Mode::List { format, .. } => output::list(&entries, format, now),
+        Mode::Agenda { days, format, .. } => output::agenda(&entries, days, format, now),
+        Mode::Next { format, .. } => output::next_due(&entries, format, now),
+        _ => return,
+    };
+    print!("{}", text);
+}
+
+fn cal(config: Config) {
+    let (parsed, store) = load_all(&config);
+    let reminders: Vec<&Reminder> = parsed
+        .reminders
+        .iter()
+        .chain(store.iter().map(|(_, r)| r))
+        .collect();
+    let now = Local::now();
+    let marked = agenda::marked(&reminders, now.year(), now.month(), now);
+    if let Some(month) = agenda::month(now.year(), now.month(), &marked) {
+        print!("{}", month);
+    }
+    if !marked.is_empty() {
+        println!("\n* has reminders");
+    }
+}
+
+fn debug_new(_config: Config) {
     println!("starting in debug mode");
 }
 
-fn debug_parser(config: Config) {
+// prints what each line parses to as a statement of a reminder file, the store is left alone
+fn debug_parser(_config: Config) {
     println!("starting in debug parser mode");
 
-    let mut buf = String::new();
-    let input = io::stdin();
+    let mut vars = parser::Vars::new();
+    let mut editor = editor();
+    while let Some(buf) = read(&mut editor) {
+        let mut lexer = Lexer::new(buf.chars(), None);
+        match parser::parse_statement(&mut lexer, &vars) {
+            Ok(Some(parser::Statement::Let(name, value))) => {
+                println!("{} = {:?}", name.text, value);
+                vars.insert(name.text, value);
+            }
+            Ok(Some(statement)) => println!("{:?}", statement),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{}^", " ".repeat(e.loc().col));
+                eprintln!("ERROR :: {}", e);
+            }
+        }
+    }
+}
 
-    let mut reminders: Vec<Reminder> = vec![];
+fn editor() -> Editor<shell::Helper, FileHistory> {
+    match Editor::new() {
+        Ok(editor) => editor,
+        Err(e) => {
+            eprintln!("ERROR :: could not start the shell :: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
 
+// a line from the shell, more lines while a string is left open. None at the end of input
+fn read(editor: &mut Editor<shell::Helper, FileHistory>) -> Option<String> {
     loop {
-        print!(">");
-        io::stdout().flush();
-        input.read_line(&mut buf).unwrap();
+        let mut buf = match editor.readline(">") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return None,
+            Err(e) => {
+                eprintln!("ERROR :: {}", e);
+                return None;
+            }
+        };
+        while shell::incomplete(&buf) {
+            match editor.readline("..") {
+                Ok(line) => {
+                    buf.push('\n');
+                    buf.push_str(&line);
+                }
+                Err(ReadlineError::Interrupted) => break,
+                // parse what there is, so the unclosed string gets reported
+                Err(_) => return Some(buf),
+            }
+        }
+        if !shell::incomplete(&buf) {
+            return Some(buf);
+        }
+    }
+}
+
+// a line of the store's list reply: id, due, state and the reminder
+fn stored(line: &str) -> Option<(String, Option<DateTime<Local>>, String, Reminder)> {
+    let mut fields = line.splitn(4, '\t');
+    let id = fields.next()?.to_string();
+    let due = DateTime::parse_from_rfc3339(fields.next()?)
+        .ok()
+        .map(|t| t.with_timezone(&Local));
+    let state = fields.next()?.to_string();
+    let reminder = parser::parse_reminder(fields.next()?).ok()?;
+    Some((id, due, state, reminder))
+}
+
+// the shell changes the store like the command line does, so undo sees its changes too
+fn shell(parsed: Parsed) {
+    let reminders = parsed.reminders;
+    let vars = parsed.vars;
+    let mut control = connect();
+    let mut editor = editor();
+    editor.set_helper(Some(shell::Helper::default()));
+    let history = shell::history_path();
+    if let Some(path) = &history {
+        // there is none the first time
+        let _ = editor.load_history(path);
+    }
+
+    loop {
+        let stored: Vec<_> = match control.request("list") {
+            Ok(reply) => reply.lines().filter_map(stored).collect(),
+            Err(e) => {
+                eprintln!("ERROR :: {}", e);
+                vec![]
+            }
+        };
+        if let Some(helper) = editor.helper_mut() {
+            helper.ids = stored
+                .iter()
+                .filter_map(|(id, ..)| id.parse().ok())
+                .collect();
+        }
+        let Some(buf) = read(&mut editor) else {
+            break;
+        };
+        if buf.trim().is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(buf.as_str());
 
         let mut lexer = Lexer::new(buf.chars(), None);
+        // what follows the command, for the requests that take it as it is
+        let rest = buf
+            .trim()
+            .split_once(char::is_whitespace)
+            .map_or("", |(_, rest)| rest);
+        let mut request =
+            |request: String, reply: fn(String) -> String| match control.request(&request) {
+                Ok(text) => {
+                    let text = reply(text);
+                    if !text.is_empty() {
+                        println!("{}", text);
+                    }
+                }
+                Err(e) => eprintln!("ERROR :: {}", e),
+            };
 
         let command = parser::get_command(&mut lexer);
 
-        match command {
-            Some(c) => {
-                match c {
-                    Command::Quit => {
-                        println!("quiting");
-                        std::process::exit(0);
-                    }
-                    Command::List => {
-                        if reminders.len() > 0 {
-                            println!("id || Reminder");
-                            for (i, r) in reminders.iter().enumerate() {
-                                println!("{}    {:?}", i, r);
-                            }
-                        } else {
-                            println!("no reminders set");
+        if let Some(c) = command {
+            match c {
+                Command::Quit => break,
+                Command::List => {
+                    // list tz shows when each reminder is next due in its own zone
+                    let zoned = lexer.peek_token().kind == TokenKind::Tz;
+                    let now = Local::now();
+                    let rows: Vec<(&str, Option<DateTime<Local>>, &str, &Reminder)> = reminders
+                        .iter()
+                        .map(|r| ("-", r.next_fire_time(now), "active", r))
+                        .chain(
+                            stored
+                                .iter()
+                                .map(|(id, due, state, r)| (id.as_str(), *due, state.as_str(), r)),
+                        )
+                        .collect();
+                    if !rows.is_empty() {
+                        println!("id || next || Reminder");
+                        for (id, due, state, r) in rows {
+                            let next = match (due, r.when().tz()) {
+                                _ if state == "done" => String::from("done"),
+                                (Some(t), Some(tz)) if zoned => {
+                                    t.with_timezone(&tz).format(TIME_FORMAT).to_string()
+                                }
+                                (Some(t), _) => t.format(TIME_FORMAT).to_string(),
+                                (None, _) => String::from("never"),
+                            };
+                            let r = r.to_string();
+                            let r = if io::stdout().is_terminal() {
+                                links::hyperlink(&r)
+                            } else {
+                                r
+                            };
+                            println!("{}    {}    {}", id, next, r);
                         }
+                    } else {
+                        println!("no reminders set");
                     }
-                    Command::Remind => match parser::parse_day(&mut lexer) {
-                        Ok(r) => {
-                            reminders.push(r);
-                        }
-                        Err(e) => match e {
-                            ParserError::NoToken(loc) => {
-                                eprint!("{}", " ".repeat(loc.col));
-                                eprintln!("{}", "^");
-                                eprintln!("ERROR :: Expected Input");
-                            }
-                            ParserError::UnclosedStr(loc, text) => {
-                                eprint!(" {}", " ".repeat(loc.col));
-                                eprintln!("{}", "^".repeat(text.len() - 1));
-                                eprintln!("ERROR :: UnclosedStr");
-                            }
-                            ParserError::UnexpectedToken(loc, got, text, expected) => {
-                                eprint!("{}", " ".repeat(loc.col));
-                                eprintln!("^^^");
-                                eprintln!("ERROR :: UnexpectedToken");
-                                eprintln!("GOT :: {:?} : {}", got, text);
-                                eprintln!("EXPECTED:: {:?}", expected);
-                            }
-                            ParserError::InvalidDay(loc, text) => {
-                                eprint!("{}", " ".repeat(loc.col));
-                                eprintln!("{}", "^".repeat(text.len() - 1));
-                                eprintln!("ERROR :: Invalid Day");
-                                eprintln!("Only shorthand works right now, eg: mon/tue/wed/thu/fri/sat/sun");
-                            }
-                            ParserError::InvalidNum(loc, num, min, max) => {
-                                eprint!("{}", " ".repeat(loc.col - 2));
-                                eprintln!("^^^");
-                                eprintln!("ERROR :: Invalid Number");
-                                eprintln!("{} is not between {}-{}", num, min, max);
-                            }
-                        },
-                    },
-                    Command::Edit => {
-                        println!("TODO!!");
-                    }
-                    Command::Help => {
-                        print_help();
+                }
+                Command::Remind => match parser::parse_remind(&mut lexer, &vars) {
+                    Ok(r) => request(format!("add {}", r), |id| format!("added {}", id)),
+                    Err(e) => {
+                        eprintln!("{}^", " ".repeat(e.loc().col));
+                        eprintln!("ERROR :: {}", e);
                     }
-                    Command::Invalid(o) => match o {
-                        Some(t) => {
-                            println!("{}{}", " ".repeat(t.loc.col), "^".repeat(t.text.len()));
-                            println!("ERROR :: invalid command '{}'", t.text);
-                        }
-                        None => {
-                            let t = lexer.peek_token();
-                            println!("{}{}", " ".repeat(t.loc.col), "^".repeat(t.text.len()));
-                            println!("ERROR :: invalid command '{}'", t.text);
+                },
+                Command::Edit => match lexer.expect_token(TokenKind::Num) {
+                    Ok(t) => match parser::parse_remind(&mut lexer, &vars) {
+                        Ok(r) => request(format!("edit {} {}", t.text, r), |reply| reply),
+                        Err(e) => {
+                            eprintln!("{}^", " ".repeat(e.loc().col));
+                            eprintln!("ERROR :: {}", e);
                         }
                     },
+                    Err(t) => {
+                        println!(
+                            "{}{}",
+                            " ".repeat(t.loc.col),
+                            "^".repeat(t.text.len().max(1))
+                        );
+                        println!("ERROR :: expected the id of a reminder, see list");
+                    }
+                },
+                Command::Delete => request(format!("rm {}", rest), |reply| reply),
+                Command::Done => request(format!("done {}", rest), |reply| reply),
+                Command::Snooze => request(format!("snooze {}", rest), |reply| reply),
+                Command::Undo => request(String::from("undo"), |r| format!("undid {}", r)),
+                Command::Redo => request(String::from("redo"), |r| format!("redid {}", r)),
+                Command::Help => {
+                    print_help();
                 }
+                Command::Invalid(o) => match o {
+                    Some(t) => {
+                        println!("{}{}", " ".repeat(t.loc.col), "^".repeat(t.text.len()));
+                        println!("ERROR :: invalid command '{}'", t.text);
+                    }
+                    None => {
+                        let t = lexer.peek_token();
+                        println!("{}{}", " ".repeat(t.loc.col), "^".repeat(t.text.len()));
+                        println!("ERROR :: invalid command '{}'", t.text);
+                    }
+                },
             }
-            None => {}
         }
+    }
 
-        buf.clear();
+    println!("quiting");
+    if let Some(path) = &history {
+        let saved = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| editor.save_history(path).map_err(io::Error::other));
+        if let Err(e) = saved {
+            eprintln!("ERROR :: could not save history :: {}", e);
+        }
     }
 }
 
-fn debug_lexer(config: Config) {
+fn debug_lexer(_config: Config) {
     println!("starting in debug lexer mode");
 
-    let mut buf = String::new();
-    let input = io::stdin();
-
-    loop {
-        print!(">");
-        io::stdout().flush();
-        input.read_line(&mut buf).unwrap();
-
-        let mut lexer = Lexer::new(buf.chars(), None);
+    let mut editor = editor();
+    while let Some(buf) = read(&mut editor) {
+        let lexer = Lexer::new(buf.chars(), None);
         for token in lexer {
             println!("{:?}", token);
         }
-
-        buf.clear();
     }
 }
 
-fn main() {
-    let config = Config::from_iter(&mut env::args());
+// the words of a statement as the shell split them. only the trigger is read the way it is
+// written, the summary and body are text whatever they look like and the words after them are
+// options. a single word is a whole statement, eg: remember add '10:00 "tea"'
+fn statement(words: &[String]) -> Result<String, String> {
+    if let [line] = words {
+        return Ok(line.clone());
+    }
+    if let Some(seconds) = words.first().and_then(|w| reminder::parse_lead(w)) {
+        return Err(format!(
+            "'{}' is not a trigger, a delay is a number of seconds eg: remember add {} \"tea\"",
+            words[0], seconds
+        ));
+    }
 
-    match config.mode {
-        Mode::Normal => normal(config),
-        Mode::Deamon => deamon(config),
-        Mode::DebugNew => debug_new(config),
-        Mode::DebugParser => debug_parser(config),
-        Mode::DebugLexer => debug_lexer(config),
+    // the longest trigger is a date, a time and a time zone
+    let bare = |word: &str| {
+        word.chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, ':' | '/'))
+            || word.chars().all(char::is_alphabetic)
+    };
+    let trigger = |words: &[String]| -> Vec<String> {
+        words
+            .iter()
+            .map(|w| {
+                if bare(w) {
+                    w.clone()
+                } else {
+                    reminder::quote(w)
+                }
+            })
+            .collect()
+    };
+    let parses = |words: &[String]| {
+        let text = trigger(words).join(" ");
+        let mut lexer = Lexer::new(text.chars(), None);
+        parser::parse_when(&mut lexer).is_ok() && parser::expect_end(&mut lexer).is_ok()
+    };
+    let Some(len) = (1..=words.len().min(4))
+        .rev()
+        .find(|k| parses(&words[..*k]))
+    else {
+        let text = trigger(words).join(" ");
+        let mut lexer = Lexer::new(text.chars(), None);
+        return match parser::parse_when(&mut lexer) {
+            Err(e) => Err(e.to_string()),
+            Ok(_) => Err(format!("could not read a trigger from '{}'", text)),
+        };
+    };
+
+    let option = |word: &str| {
+        matches!(
+            word,
+            "warn" | "urgency" | "icon" | "timeout" | "category" | "from"
+        ) || (word.starts_with(['+', '@', '!']) && !word.contains(char::is_whitespace))
+    };
+    let mut out = trigger(&words[..len]);
+    let mut rest = words[len..].iter().peekable();
+    if let Some(summary) = rest.next() {
+        out.push(reminder::quote(summary));
+    }
+    if let Some(body) = rest.next_if(|w| !option(w)) {
+        out.push(reminder::quote(body));
     }
+    for word in rest {
+        let plain = word.chars().all(|c| c.is_alphanumeric() || c == ',');
+        out.push(if plain || option(word) {
+            word.clone()
+        } else {
+            reminder::quote(word)
+        });
+    }
+    Ok(out.join(" "))
 }
 
-fn main2() {
-    libnotify::init("Remember");
+// add, edit, rm, done, snooze, undo and redo, exits when the request fails
+fn send(request: &str) -> String {
+    connect().request(request).unwrap_or_else(|e| {
+        eprintln!("ERROR :: {}", e);
+        std::process::exit(1);
+    })
+}
 
-    let time = remind!(10:30 "this is a time test" "ljlj"); // Reminder::new(When::Time(10, 30), "this is a time test", Some("ljlj"))
-    let day = remind!(wed 11:25 "this is a day test"); // Reminder::new(When::Day("Tue".to_string(), 11, 25), "this is a day test", None);
-    let date = remind!(16/5/2022 11:25 "this is a date test"); // Reminder::new(When::Date(16, 5, 2022, 11, 25), "this is a date test", None);
-    let duration = remind!(3 "this is a duration test"); // Reminder::new(When::Duration(3), "this is a duration test", None);
-    let url = remind!(3 "<https://google.com>"); // Reminder::new(When::Duration(2), "url test", Some("<https://google.com>"));
+// what the daemon showed in the last `since` seconds, or ever
+fn notifications(since: Option<u32>, format: output::Format) {
+    let Some(path) = history::default_path() else {
+        eprintln!("ERROR :: no home directory to keep the history in");
+        std::process::exit(1);
+    };
+    let since = since.map(|s| Local::now() - chrono::Duration::seconds(s as i64));
+    match History::new(path.clone()).read(since) {
+        Ok(deliveries) => print!("{}", output::history(&deliveries, format)),
+        Err(e) => {
+            eprintln!("ERROR :: could not read {} :: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
 
-    let mut reminders = vec![time, day, date, duration, url];
-    let mut quit = false;
+// the event log, oldest first
+fn events(id: Option<u32>) {
+    let reply = send(&format!(
+        "events {}",
+        id.map_or(String::new(), |id| id.to_string())
+    ));
+    for line in reply.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        let [time, kind, id] = fields[..] else {
+            continue;
+        };
+        let time = DateTime::parse_from_rfc3339(time).map_or(time.to_string(), |t| {
+            t.with_timezone(&Local).format(TIME_FORMAT).to_string()
+        });
+        println!("{}  {:<9}  {}", time, kind, id);
+    }
+}
 
-    while reminders.len() > 0 && !quit {
-        thread::sleep(time::Duration::from_secs(1));
-        let now = Local::now();
-        for i in 0..reminders.len() {
-            let mut r = &mut reminders[i];
-            if !r.is_done() && r.check(now) {
-                r.show().unwrap();
+fn control(request: &str) {
+    let reply = send(request);
+    if !reply.is_empty() {
+        println!("{}", reply);
+    }
+}
+
+fn main() {
+    let mut config = Config::parse();
+
+    match config.mode.take() {
+        None | Some(Mode::Shell) => normal(config),
+        Some(Mode::Add { statement: words }) => match statement(&words) {
+            Ok(statement) => control(&format!("add remind {}", statement)),
+            Err(e) => {
+                eprintln!("ERROR :: {}", e);
+                std::process::exit(1);
             }
+        },
+        Some(Mode::Edit {
+            id,
+            statement: words,
+        }) => match statement(&words) {
+            Ok(statement) => control(&format!("edit {} remind {}", id, statement)),
+            Err(e) => {
+                eprintln!("ERROR :: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Some(Mode::Rm {
+            filter: Some(text), ..
+        }) => control(&format!("rm where {}", text)),
+        Some(Mode::Rm { ids, .. }) => {
+            for id in ids {
+                control(&format!("rm {}", id));
+            }
+        }
+        Some(Mode::Done { id }) => control(&format!("done {}", id)),
+        Some(Mode::Undo) => println!("undid {}", send("undo")),
+        Some(Mode::Redo) => println!("redid {}", send("redo")),
+        Some(Mode::Events { id }) => events(id),
+        Some(Mode::History { since, format }) => notifications(since, format),
+        Some(Mode::Snooze {
+            id,
+            duration,
+            span,
+            filter,
+        }) => {
+            let duration = duration.or(span).unwrap_or(String::from("10m"));
+            match (filter, id) {
+                (Some(text), _) => control(&format!("snooze {} where {}", duration, text)),
+                (None, Some(id)) => control(&format!("snooze {} {}", id, duration)),
+                (None, None) => {}
+            }
+        }
+        Some(Mode::Daemon { opener }) => deamon(config, &opener),
+        Some(Mode::Import { file }) => import(file),
+        Some(Mode::Export { format, select }) => export(config, &format, select),
+        Some(Mode::Tui) => tui(),
+        Some(mode @ (Mode::List { .. } | Mode::Agenda { .. } | Mode::Next { .. })) => {
+            report(config, mode)
         }
+        Some(Mode::Cal) => cal(config),
+        Some(Mode::Debug { mode: Debug::New }) => debug_new(config),
+        Some(Mode::Debug {
+            mode: Debug::Parser,
+        }) => debug_parser(config),
+        Some(Mode::Debug { mode: Debug::Lexer }) => debug_lexer(config),
     }
+}
 
-    libnotify::uninit();
+#[allow(dead_code)]
+fn main2() {
+    let time = remind!(10:30 "this is a time test" "ljlj"); // Reminder::new(When::Time(10, 30), "this is a time test", Some("ljlj"))
+    let day = remind!(wed 11:25 "this is a day test"); // Reminder::new(When::Day("Tue".to_string(), 11, 25), "this is a day test", None);
+    let date = remind!(16/5/2022 11:25 "this is a date test"); // Reminder::new(When::Date(16, 5, 2022, 11, 25), "this is a date test", None);
+    let duration = remind!(3 "this is a duration test"); // Reminder::new(When::Duration(3), "this is a duration test", None);
+    let url = remind!(3 "url test" "<https://google.com>"); // Reminder::new(When::Duration(2), "url test", Some("<https://google.com>"));
+    let cron = remind!(cron "*/5 * * * *" "this is a cron test"); // Reminder::new(When::Cron("*/5 * * * *".parse().unwrap()), "this is a cron test", None);
+
+    let parsed = Parsed {
+        reminders: vec![time, day, date, duration, url, cron],
+        ..Default::default()
+    };
+    run(parsed, &[], Store::new(None), "xdg-open");
 }
@@ -0,0 +1,267 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt::Display,
+    io::{BufRead, BufReader, IsTerminal},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use chrono::Local;
+use libnotify::Notification;
+
+use crate::{
+    history::{Delivery, History, Kind},
+    links,
+};
+
+// what a reminder looks like when it goes off, handed to whichever backend shows it
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Urgency {
+    Low,
+    #[default]
+    Normal,
+    Critical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub summary: String,
+    pub body: Option<String>,
+    pub urgency: Urgency,
+    pub icon: Option<String>,
+    pub timeout: Option<u32>, //seconds, 0 never expires and None is the server default
+    pub category: Option<String>,
+    pub urls: Vec<String>, //links in the body, a backend can offer to open them
+    pub id: Option<u32>,   //the store's, for the history
+}
+
+pub trait Backend {
+    // id is the same every time a reminder goes off, so a backend can replace the last notice
+    fn show(&mut self, id: &str, notice: &Notice) -> Result<(), String>;
+
+    // what the notification history calls it
+    fn name(&self) -> &str {
+        "other"
+    }
+}
+
+// desktop notifications, one per reminder that is updated every time it goes off
+pub struct Libnotify {
+    notifs: HashMap<String, Notification>,
+    app_name: String,
+    opener: String,           //command links are opened with
+    actions: bool,            //whether the notification server shows actions
+    history: Option<History>, //where links being opened are recorded
+    shown_by: &'static str,   //what showed the last notice, for the history
+}
+
+impl Libnotify {
+    pub fn new(app_name: &str, opener: &str) -> Result<Self, String> {
+        libnotify::init(app_name)?;
+        Ok(Libnotify {
+            notifs: HashMap::new(),
+            app_name: app_name.to_string(),
+            opener: opener.to_string(),
+            actions: libnotify::get_server_caps().iter().any(|c| c == "actions"),
+            history: None,
+            shown_by: "libnotify",
+        })
+    }
+
+    pub fn set_history(&mut self, history: History) {
+        self.history = Some(history);
+    }
+
+    // the libnotify bindings cant add actions, so a notice with links is shown by notify-send,
+    // which waits for an action to be picked and prints its name
+    fn show_with_actions(&self, notice: &Notice) -> std::io::Result<Child> {
+        let mut command = Command::new("notify-send");
+        command
+            .arg("--app-name")
+            .arg(&self.app_name)
+            .arg("--urgency")
+            .arg(notice.urgency.to_string())
+            .arg("--wait");
+        if let Some(icon) = &notice.icon {
+            command.arg("--icon").arg(icon);
+        }
+        if let Some(timeout) = notice.timeout {
+            command
+                .arg("--expire-time")
+                .arg(timeout.saturating_mul(1000).to_string());
+        }
+        if let Some(category) = &notice.category {
+            command.arg("--category").arg(category);
+        }
+        for (i, url) in notice.urls.iter().enumerate() {
+            let label = match notice.urls.len() {
+                1 => String::from("Open"),
+                _ => format!("Open {}", url),
+            };
+            command.arg("--action").arg(format!("{}={}", i, label));
+        }
+        command
+            .arg("--")
+            .arg(&notice.summary)
+            .arg(notice.body.as_deref().unwrap_or(""))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .spawn()
+    }
+}
+
+// notify-send keeps running until the notice is closed but exits straight away when it cant
+// show it, so one that is still running after a moment has shown it
+fn started(child: &mut Child) -> Result<(), String> {
+    for _ in 0..20 {
+        match child.try_wait() {
+            Ok(Some(status)) if !status.success() => {
+                return Err(format!("notify-send exited with {}", status))
+            }
+            Ok(Some(_)) => return Ok(()),
+            Ok(None) => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+    Ok(())
+}
+
+impl Backend for Libnotify {
+    fn show(&mut self, id: &str, notice: &Notice) -> Result<(), String> {
+        if self.actions && !notice.urls.is_empty() {
+            // falls back to a notification without actions when notify-send isnt there or fails
+            let child = match self.show_with_actions(notice) {
+                Ok(mut child) => started(&mut child).map(|_| Some(child)),
+                Err(_) => Ok(None),
+            };
+            if let Err(e) = &child {
+                eprintln!("ERROR :: {}", e);
+            }
+            if let Ok(Some(mut child)) = child {
+                self.shown_by = "notify-send";
+                let id = notice.id;
+                let opener = self.opener.clone();
+                let urls = notice.urls.clone();
+                let history = self.history.clone();
+                let summary = notice.summary.clone();
+                thread::spawn(move || {
+                    if let Some(stdout) = child.stdout.take() {
+                        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                            let url = line.trim().parse::<usize>().ok().and_then(|i| urls.get(i));
+                            let Some(url) = url else {
+                                continue;
+                            };
+                            let opened = links::open(&opener, url);
+                            if let Err(e) = &opened {
+                                eprintln!("ERROR :: {}", e);
+                            }
+                            let Some(history) = &history else {
+                                continue;
+                            };
+                            let delivery = Delivery {
+                                time: Local::now(),
+                                id,
+                                kind: Kind::Action,
+                                backend: String::from("notify-send"),
+                                result: opened,
+                                action: Some(format!("opened {}", url)),
+                                summary: summary.clone(),
+                            };
+                            if let Err(e) = history.record(&delivery) {
+                                eprintln!("ERROR :: could not record the history :: {}", e);
+                            }
+                        }
+                    }
+                    child.wait()
+                });
+                return Ok(());
+            }
+        }
+
+        self.shown_by = "libnotify";
+        let body = notice.body.as_deref();
+        let icon = notice.icon.as_deref();
+        let notif = match self.notifs.entry(id.to_string()) {
+            Entry::Occupied(entry) => {
+                let notif = entry.into_mut();
+                notif.update(&notice.summary, body, icon)?;
+                notif
+            }
+            Entry::Vacant(entry) => entry.insert(Notification::new(&notice.summary, body, icon)),
+        };
+        notif.set_urgency(match notice.urgency {
+            Urgency::Low => libnotify::Urgency::Low,
+            Urgency::Normal => libnotify::Urgency::Normal,
+            Urgency::Critical => libnotify::Urgency::Critical,
+        });
+        notif.set_timeout(match notice.timeout {
+            Some(seconds) => seconds.saturating_mul(1000).min(i32::MAX as u32) as i32,
+            None => -1,
+        });
+        if let Some(category) = &notice.category {
+            notif.set_category(category);
+        }
+        notif.show().map_err(|e| e.to_string())
+    }
+
+    fn name(&self) -> &str {
+        self.shown_by
+    }
+}
+
+impl Drop for Libnotify {
+    fn drop(&mut self) {
+        self.notifs.clear();
+        libnotify::uninit();
+    }
+}
+
+// prints every notice, for when there is no notification server
+pub struct Print;
+
+impl Backend for Print {
+    fn show(&mut self, _id: &str, notice: &Notice) -> Result<(), String> {
+        match &notice.body {
+            Some(body) if std::io::stdout().is_terminal() => println!(
+                "[{}] {}: {}",
+                notice.urgency,
+                notice.summary,
+                links::hyperlink(body)
+            ),
+            Some(body) => println!("[{}] {}: {}", notice.urgency, notice.summary, body),
+            None => println!("[{}] {}", notice.urgency, notice.summary),
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "print"
+    }
+}
+
+impl Display for Urgency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Urgency::Low => write!(f, "low"),
+            Urgency::Normal => write!(f, "normal"),
+            Urgency::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+impl FromStr for Urgency {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Urgency::Low),
+            "normal" => Ok(Urgency::Normal),
+            "critical" => Ok(Urgency::Critical),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::{fmt::Write, str::FromStr};
+
+use chrono::{DateTime, Local};
+
+use crate::{agenda, control, history::Delivery, Reminder};
+
+// list, agenda and next for scripts and status bars as well as people.
+// a reminder in json is always
+//   {"id": 3, "summary": "...", "body": "..." or null, "trigger": "mon 9:00",
+//    "next": "2022-05-16T09:00:00+10:00" or null, "state": "active", "tags": ["+work"],
+//    "priority": "normal"}
+// id is null for reminders from a reminder file, only the store hands out ids.
+// a notification from the history in json is
+//   {"time": "2022-05-16T09:00:00+10:00", "id": 3, "kind": "reminder", "backend": "libnotify",
+//    "error": null, "action": "silenced" or null, "summary": "..."}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Plain,
+    Json,
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Format::Plain),
+            "json" => Ok(Format::Json),
+            "tsv" => Ok(Format::Tsv),
+            _ => Err(format!("unknown format '{}', expected plain/json/tsv", s)),
+        }
+    }
+}
+
+// a reminder and its id in the store, if it is from there
+#[derive(Debug, Clone, Copy)]
+pub struct Entry<'a> {
+    pub id: Option<u32>,
+    pub reminder: &'a Reminder,
+}
+
+// every reminder, by when it is next due
+pub fn list(entries: &[Entry], format: Format, now: DateTime<Local>) -> String {
+    let mut entries = entries.to_vec();
+    entries.sort_by_key(|e| {
+        let next = next(e.reminder, now);
+        (next.is_none(), next)
+    });
+    let mut out = String::new();
+    match format {
+        Format::Json => {
+            let items: Vec<String> = entries.iter().map(|e| json(e, now)).collect();
+            writeln!(out, "[{}]", items.join(",")).unwrap();
+        }
+        Format::Tsv => {
+            for e in &entries {
+                writeln!(out, "{}", tsv(e, now)).unwrap();
+            }
+        }
+        Format::Plain => {
+            for e in &entries {
+                let when = match next(e.reminder, now) {
+                    Some(t) => format!(
+                        "{}  {:<8}",
+                        t.format("%a %d/%m %H:%M"),
+                        agenda::relative(t, now)
+                    ),
+                    None => format!("{:<25}", control::state(e.reminder)),
+                };
+                writeln!(out, "{:>4}  {}  {}", id(e), when, e.reminder.summary()).unwrap();
+            }
+        }
+    }
+    out
+}
+
+// the occurrences in the next `days` days
+pub fn agenda(entries: &[Entry], days: u32, format: Format, now: DateTime<Local>) -> String {
+    let reminders: Vec<&Reminder> = entries.iter().map(|e| e.reminder).collect();
+    let agenda = agenda::agenda(&reminders, now, days);
+    let entry = |r| find(entries, r);
+    let mut out = String::new();
+    match format {
+        Format::Json => {
+            let items: Vec<String> = agenda
+                .iter()
+                .flat_map(|(_, list)| list)
+                .map(|(time, r)| {
+                    format!(
+                        "{{\"at\":{},\"reminder\":{}}}",
+                        string(&time.to_rfc3339()),
+                        json(&entry(r), now)
+                    )
+                })
+                .collect();
+            writeln!(out, "[{}]", items.join(",")).unwrap();
+        }
+        Format::Tsv => {
+            for (time, r) in agenda.iter().flat_map(|(_, list)| list) {
+                writeln!(out, "{}\t{}", time.to_rfc3339(), tsv(&entry(r), now)).unwrap();
+            }
+        }
+        Format::Plain => {
+            let today = now.naive_local().date();
+            if agenda.is_empty() {
+                writeln!(out, "nothing in the next {} days", days).unwrap();
+            }
+            for (i, (date, list)) in agenda.iter().enumerate() {
+                if i > 0 {
+                    out.push('\n');
+                }
+                let name = match (*date - today).num_days() {
+                    0 => " (today)",
+                    1 => " (tomorrow)",
+                    _ => "",
+                };
+                writeln!(out, "{}{}", date.format("%a %d/%m/%Y"), name).unwrap();
+                for (time, r) in list {
+                    let relative = agenda::relative(*time, now);
+                    writeln!(
+                        out,
+                        "  {}  {:<8} {}",
+                        time.format("%H:%M"),
+                        relative,
+                        r.summary()
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+// the reminder due soonest, made to fit in a status bar. nothing at all if there is none,
+// or json null
+pub fn next_due(entries: &[Entry], format: Format, now: DateTime<Local>) -> String {
+    let soonest = entries
+        .iter()
+        .filter_map(|e| next(e.reminder, now).map(|t| (t, e)))
+        .min_by_key(|(t, _)| *t);
+    match (format, soonest) {
+        (Format::Json, Some((_, e))) => format!("{}\n", json(e, now)),
+        (Format::Json, None) => String::from("null\n"),
+        (Format::Tsv, Some((_, e))) => format!("{}\n", tsv(e, now)),
+        (Format::Plain, Some((t, e))) => format!(
+            "{} {} ({})\n",
+            t.format("%H:%M"),
+            e.reminder.summary(),
+            agenda::relative(t, now)
+        ),
+        (_, None) => String::new(),
+    }
+}
+
+// what the daemon showed, oldest first
+pub fn history(deliveries: &[Delivery], format: Format) -> String {
+    let null = || String::from("null");
+    let mut out = String::new();
+    match format {
+        Format::Json => {
+            let items: Vec<String> = deliveries
+                .iter()
+                .map(|d| {
+                    format!(
+                        "{{\"time\":{},\"id\":{},\"kind\":{},\"backend\":{},\"error\":{},\"action\":{},\"summary\":{}}}",
+                        string(&d.time.to_rfc3339()),
+                        d.id.map_or_else(null, |id| id.to_string()),
+                        string(&d.kind.to_string()),
+                        string(&d.backend),
+                        d.result.as_ref().err().map_or_else(null, |e| string(e)),
+                        d.action.as_deref().map_or_else(null, string),
+                        string(&d.summary)
+                    )
+                })
+                .collect();
+            writeln!(out, "[{}]", items.join(",")).unwrap();
+        }
+        Format::Tsv => {
+            for d in deliveries {
+                writeln!(out, "{}", d.line()).unwrap();
+            }
+        }
+        Format::Plain => {
+            if deliveries.is_empty() {
+                writeln!(out, "nothing was shown").unwrap();
+            }
+            for d in deliveries {
+                let id = d.id.map_or(String::from("-"), |id| id.to_string());
+                write!(
+                    out,
+                    "{}  {:>4}  {:<8}  {}",
+                    d.time.format("%a %d/%m %H:%M"),
+                    id,
+                    d.kind.to_string(),
+                    d.summary
+                )
+                .unwrap();
+                if let Some(action) = &d.action {
+                    write!(out, " ({})", action).unwrap();
+                }
+                if let Err(e) = &d.result {
+                    write!(out, " failed: {}", e).unwrap();
+                }
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+// when it next goes off, nothing if it is done
+fn next(r: &Reminder, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    if r.is_done() {
+        return None;
+    }
+    r.due(now)
+}
+
+// the entry a reminder in the agenda came from
+fn find<'a>(entries: &[Entry<'a>], r: &'a Reminder) -> Entry<'a> {
+    entries
+        .iter()
+        .find(|e| std::ptr::eq(e.reminder, r))
+        .copied()
+        .unwrap_or(Entry {
+            id: None,
+            reminder: r,
+        })
+}
+
+fn id(e: &Entry) -> String {
+    e.id.map_or(String::from("-"), |id| id.to_string())
+}
+
+pub fn json(e: &Entry, now: DateTime<Local>) -> String {
+    let r = e.reminder;
+    let null = || String::from("null");
+    let tags: Vec<String> = r.tags().iter().map(|t| string(t)).collect();
+    format!(
+        "{{\"id\":{},\"summary\":{},\"body\":{},\"trigger\":{},\"next\":{},\"state\":{},\"tags\":[{}],\"priority\":{}}}",
+        e.id.map_or_else(null, |id| id.to_string()),
+        string(r.summary()),
+        r.body().map_or_else(null, string),
+        string(&r.when().to_string()),
+        next(r, now).map_or_else(null, |t| string(&t.to_rfc3339())),
+        string(control::state(r)),
+        tags.join(","),
+        string(&r.priority().to_string())
+    )
+}
+
+// id, next, state, summary, body, trigger, tags and priority. tabs and newlines in the text
+// become spaces, tags are split by spaces
+fn tsv(e: &Entry, now: DateTime<Local>) -> String {
+    let r = e.reminder;
+    let clean = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        id(e),
+        next(r, now).map_or(String::from("-"), |t| t.to_rfc3339()),
+        control::state(r),
+        clean(r.summary()),
+        r.body().map(clean).unwrap_or_default(),
+        r.when(),
+        r.tags().join(" "),
+        r.priority()
+    )
+}
+
+// a json string, quoted and escaped
+pub fn string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,6 +1,17 @@
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::NaiveDate;
+
 use crate::{
     lexer::{Lexer, Loc, Token, TokenKind},
-    loc_here, Reminder,
+    quiet::{Action, Quiet, Window},
+    reminder, template, Reminder, When,
 };
 
 pub fn get_command<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Option<Command> {
@@ -12,285 +23,681 @@ pub fn get_command<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Option<Com
             TokenKind::List => Some(Command::List),
             TokenKind::Edit => Some(Command::Edit),
             TokenKind::Help => Some(Command::Help),
-            _ => Some(Command::Invalid(lexer.next())),
+            // plain words rather than keywords, so they stay free for summaries and filters
+            TokenKind::Str => match token.text.as_str() {
+                "rm" | "delete" => Some(Command::Delete),
+                "done" => Some(Command::Done),
+                "snooze" => Some(Command::Snooze),
+                "undo" => Some(Command::Undo),
+                "redo" => Some(Command::Redo),
+                _ => Some(Command::Invalid(Some(token))),
+            },
+            TokenKind::End => None,
+            _ => Some(Command::Invalid(Some(token))),
         },
         None => None,
     }
 }
 
-pub fn parse_duration<C: Iterator<Item = char>>(
-    lexer: &mut Lexer<C>,
-) -> Result<Reminder, ParserError> {
-    match lexer.expect_token(TokenKind::Num) {
-        Ok(token) => {
-            let duration = token.text.parse::<u32>().unwrap();
-            let summary = match lexer.expect_token(TokenKind::Str) {
-                Ok(t) => t.text,
-                Err(t) => match t {
-                    Token {
-                        kind: TokenKind::End,
-                        ..
-                    } => String::from("no summary provided"),
-                    Token {
-                        kind: TokenKind::UnclosedStr,
-                        text,
-                        loc,
-                    } => return Err(ParserError::UnclosedStr(loc, text)),
-                    Token { kind, text, loc } => {
-                        return Err(ParserError::UnexpectedToken(
-                            loc,
-                            kind,
-                            text,
-                            TokenKind::Str,
-                        ));
-                    }
-                },
-            };
-            let description = match lexer.next() {
-                Some(t) => match t {
-                    Token {
-                        kind: TokenKind::Str,
-                        text,
-                        ..
-                    } => Some(text),
-                    Token {
-                        kind: TokenKind::UnclosedStr,
-                        text,
-                        loc,
-                    } => {
-                        return Err(ParserError::UnclosedStr(loc, text));
-                    }
-                    _ => None,
-                },
-                None => None,
-            };
-            Ok(Reminder::new(
-                crate::When::Duration(duration),
-                summary,
-                description,
-            ))
+#[derive(Default)]
+pub struct Parsed {
+    pub reminders: Vec<Reminder>,
+    pub errors: Vec<ParserError>,
+    pub files: Vec<PathBuf>, //every file that was read, includes too
+    pub vars: Vars,
+    pub quiet: Quiet,
+}
+
+#[derive(Debug)]
+pub enum Statement {
+    Remind(Box<Reminder>),
+    Include(Token),
+    Let(Token, String), //name, value
+    Quiet(Window),
+    Urgent(String), //tag
+}
+
+// variables defined with let, builtins are never in here
+pub type Vars = HashMap<String, String>;
+
+pub const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+pub fn parse_file<P: AsRef<Path>>(path: P) -> io::Result<Parsed> {
+    parse_paths(&[path])
+}
+
+// loads every path in order, directories are loaded whole like a conf.d
+pub fn parse_paths<P: AsRef<Path>>(paths: &[P]) -> io::Result<Parsed> {
+    let mut loader = Loader::default();
+    for path in paths {
+        let path = path.as_ref();
+        if path.is_dir() {
+            loader.dir(path)
+        } else {
+            loader.file(path)
         }
-        Err(token) => match token {
-            Token {
-                kind: TokenKind::UnclosedStr,
-                text,
-                loc,
-            } => Err(ParserError::UnclosedStr(loc, text)),
-            Token {
-                kind: TokenKind::End,
-                loc,
-                ..
-            } => Err(ParserError::NoToken(loc)),
-            Token { kind, text, loc } => Err(ParserError::UnexpectedToken(
-                loc,
-                kind,
-                text,
-                TokenKind::Str,
-            )),
-        },
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
     }
+    Ok(loader.parsed)
 }
 
-pub fn parse_time<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Reminder, ParserError> {
-    match lexer.expect_token(TokenKind::Num) {
-        Ok(token) => {
-            let hour = token.text.parse::<u32>().unwrap();
-            if hour > 12 {
-                return Err(ParserError::InvalidNum(token.loc, hour as i32, 0, 12));
+// unlike the first load, a reload that has any errors is thrown away and only the errors are
+// given back, so the reminders that are live stay as they are
+pub fn reload<P: AsRef<Path>>(paths: &[P]) -> io::Result<Result<Parsed, Vec<ParserError>>> {
+    let parsed = parse_paths(paths)?;
+    if parsed.errors.is_empty() {
+        Ok(Ok(parsed))
+    } else {
+        Ok(Err(parsed.errors))
+    }
+}
+
+#[derive(Default)]
+struct Loader {
+    parsed: Parsed,
+    stack: Vec<PathBuf>, //files currently being loaded, for cycle detection
+}
+
+impl Loader {
+    fn dir(&mut self, dir: &Path) -> io::Result<()> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_file() && !is_skipped(&path) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        // one file that cant be read doesnt stop the rest of the directory loading
+        for path in paths {
+            if let Err(e) = self.file(&path) {
+                let loc = Loc {
+                    file_path: Some(path.display().to_string()),
+                    row: 1,
+                    col: 1,
+                };
+                self.parsed
+                    .errors
+                    .push(ParserError::ReadFailed(loc, e.to_string()));
             }
-            let minute = match lexer.expect_token(TokenKind::Num) {
-                Ok(token) => token.text.parse::<u32>().unwrap(),
-                Err(token) => {
-                    return Err(ParserError::UnexpectedToken(
-                        token.loc,
-                        token.kind,
-                        token.text,
-                        TokenKind::Num,
-                    ))
+        }
+        Ok(())
+    }
+
+    fn file(&mut self, path: &Path) -> io::Result<()> {
+        let content = fs::read_to_string(path)?;
+        let canonical = path.canonicalize()?;
+        if self.parsed.files.contains(&canonical) {
+            return Ok(());
+        }
+        self.parsed.files.push(canonical.clone());
+        self.stack.push(canonical);
+
+        let file_path = path.display().to_string();
+        for (lnum, line) in content.lines().enumerate() {
+            let mut lexer = Lexer::new_at_line(line.chars(), Some(file_path.clone()), lnum);
+            // variables are shared by every file, in the order they are loaded
+            match parse_statement(&mut lexer, &self.parsed.vars) {
+                Ok(Some(Statement::Remind(reminder))) => self.parsed.reminders.push(*reminder),
+                Ok(Some(Statement::Include(token))) => self.include(path, token),
+                Ok(Some(Statement::Let(name, value))) => {
+                    self.parsed.vars.insert(name.text, value);
                 }
-            };
-            if minute > 59 {
-                return Err(ParserError::InvalidNum(token.loc, minute as i32, 0, 59));
+                Ok(Some(Statement::Quiet(window))) => self.parsed.quiet.windows.push(window),
+                Ok(Some(Statement::Urgent(tag))) => self.parsed.quiet.urgent.push(tag),
+                Ok(None) => {}
+                Err(e) => self.parsed.errors.push(e),
             }
-            let summary = match lexer.expect_token(TokenKind::Str) {
-                Ok(token) => token.text,
-                Err(token) => match token {
-                    Token {
-                        kind: TokenKind::End,
-                        ..
-                    } => String::from("no summary provided"),
-                    Token {
-                        kind: TokenKind::UnclosedStr,
-                        text,
-                        loc,
-                    } => return Err(ParserError::UnclosedStr(loc, text)),
-                    Token { kind, text, loc } => {
-                        return Err(ParserError::UnexpectedToken(
-                            loc,
-                            kind,
-                            text,
-                            TokenKind::Str,
-                        ))
-                    }
-                },
-            };
-            let description = match lexer.next() {
-                Some(token) => match token {
-                    Token {
-                        kind: TokenKind::Str,
-                        text,
-                        ..
-                    } => Some(text),
-                    Token {
-                        kind: TokenKind::UnclosedStr,
-                        text,
-                        loc,
-                    } => return Err(ParserError::UnclosedStr(loc, text)),
-                    _ => None,
-                },
-                None => None,
-            };
-            Ok(Reminder::new(
-                crate::When::Time(hour, minute),
-                summary,
-                description,
-            ))
         }
 
-        Err(token) => match token {
-            Token {
-                kind: TokenKind::UnclosedStr,
-                text,
-                loc,
-            } => Err(ParserError::UnclosedStr(loc, text)),
-            Token {
-                kind: TokenKind::End,
-                loc,
-                ..
-            } => Err(ParserError::NoToken(loc)),
-            Token { kind, text, loc } => Err(ParserError::UnexpectedToken(
-                loc,
-                kind,
-                text,
-                TokenKind::Str,
-            )),
-        },
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn include(&mut self, from: &Path, token: Token) {
+        let path = resolve(from, &token.text);
+        if let Ok(canonical) = path.canonicalize() {
+            if self.stack.contains(&canonical) {
+                self.parsed
+                    .errors
+                    .push(ParserError::IncludeCycle(token.loc, token.text));
+                return;
+            }
+        }
+        if let Err(e) = self.file(&path) {
+            self.parsed.errors.push(ParserError::IncludeFailed(
+                token.loc,
+                token.text,
+                e.to_string(),
+            ));
+        }
     }
 }
 
-pub fn parse_day<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Reminder, ParserError> {
-    match lexer.expect_token(TokenKind::Str) {
-        Ok(token) => {
-            let day = token.text;
-            if !"montuewedthufrisatsun".contains(&day) {
-                return Err(ParserError::InvalidDay(token.loc, day));
+// hidden and editor backup files in a conf.d directory
+pub fn is_skipped(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.starts_with('.') || name.ends_with('~')
+}
+
+// include paths are relative to the file they are in, ~/ is the home directory
+fn resolve(from: &Path, include: &str) -> PathBuf {
+    if let (Some(rest), Some(home)) = (include.strip_prefix("~/"), env::var_os("HOME")) {
+        return PathBuf::from(home).join(rest);
+    }
+    match from.parent() {
+        Some(dir) => dir.join(include),
+        None => PathBuf::from(include),
+    }
+}
+
+// a single remind statement, as the store and control channel keep them
+pub fn parse_reminder(line: &str) -> Result<Reminder, ParserError> {
+    let mut lexer = Lexer::new(line.chars(), None);
+    expect(&mut lexer, TokenKind::Remind)?;
+    parse_remind(&mut lexer, &Vars::new())
+}
+
+pub fn parse_statement<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    vars: &Vars,
+) -> Result<Option<Statement>, ParserError> {
+    match lexer.peek_token().kind {
+        TokenKind::End => Ok(None),
+        TokenKind::Remind => {
+            lexer.next();
+            Ok(Some(Statement::Remind(Box::new(parse_remind(
+                lexer, vars,
+            )?))))
+        }
+        TokenKind::Let => {
+            lexer.next();
+            let name = expect(lexer, TokenKind::Str)?;
+            expect(lexer, TokenKind::Equals)?;
+            let value = expand(expect(lexer, TokenKind::Str)?, vars)?;
+            expect_end(lexer)?;
+            Ok(Some(Statement::Let(name, value)))
+        }
+        TokenKind::Include => {
+            lexer.next();
+            let path = expect(lexer, TokenKind::Str)?;
+            expect_end(lexer)?;
+            Ok(Some(Statement::Include(path)))
+        }
+        TokenKind::Quiet => {
+            lexer.next();
+            Ok(Some(Statement::Quiet(parse_quiet(lexer)?)))
+        }
+        TokenKind::Urgent => {
+            lexer.next();
+            let tag = expect(lexer, TokenKind::Str)?;
+            expect_end(lexer)?;
+            Ok(Some(Statement::Urgent(tag.text)))
+        }
+        _ => Err(unexpected(lexer.next_token(), TokenKind::Remind)),
+    }
+}
+
+// everything after the remind keyword: <when> <summary> [body]
+pub fn parse_remind<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    vars: &Vars,
+) -> Result<Reminder, ParserError> {
+    let when = parse_when(lexer)?;
+    let summary = parse_summary(lexer, vars)?;
+    let body = parse_body(lexer, vars)?;
+    let mut reminder = Reminder::new(when, summary, body);
+    parse_options(lexer, &mut reminder)?;
+    expect_end(lexer)?;
+    Ok(reminder)
+}
+
+// everything after the quiet keyword: [days] <from> <to> [defer|drop|silent] [tag "name"]
+fn parse_quiet<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Window, ParserError> {
+    let mut days = vec![];
+    while lexer.peek_token().kind == TokenKind::Str {
+        days.push(parse_weekday(lexer.next_token())?.parse().unwrap());
+        if lexer.peek_token().kind == TokenKind::Comma {
+            lexer.next_token();
+        }
+    }
+    let from = parse_clock(lexer)?;
+    let to = parse_clock(lexer)?;
+
+    let mut window = Window {
+        days,
+        from,
+        to,
+        action: Action::Defer,
+        tag: None,
+    };
+    while lexer.peek_token().kind == TokenKind::Str {
+        let token = lexer.next_token();
+        match token.text.as_str() {
+            "defer" => window.action = Action::Defer,
+            "drop" => window.action = Action::Drop,
+            "silent" => window.action = Action::Silent,
+            "tag" => window.tag = Some(expect(lexer, TokenKind::Str)?.text),
+            _ => return Err(ParserError::InvalidQuiet(token.loc, token.text)),
+        }
+    }
+    expect_end(lexer)?;
+    Ok(window)
+}
+
+// the options after the body, in any order:
+//   +tag @tag / !low|!normal|!high / warn 15m, 1d / urgency low|normal|critical / icon "name" /
+//   timeout 30s|never / category "name" / from day/month/year
+fn parse_options<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    reminder: &mut Reminder,
+) -> Result<(), ParserError> {
+    loop {
+        match lexer.peek_token().kind {
+            TokenKind::Tag => {
+                let mut tags = reminder.tags().to_vec();
+                tags.push(lexer.next_token().text);
+                reminder.set_tags(tags);
+            }
+            TokenKind::Priority => {
+                let token = lexer.next_token();
+                match token.text[1..].parse() {
+                    Ok(priority) => reminder.set_priority(priority),
+                    Err(_) => return Err(ParserError::InvalidPriority(token.loc, token.text)),
+                }
             }
-            let hour = match lexer.expect_token(TokenKind::Num) {
-                Ok(token) => token.text.parse::<u32>().unwrap(),
-                Err(token) => {
-                    return Err(ParserError::UnexpectedToken(
-                        token.loc,
-                        token.kind,
-                        token.text,
-                        TokenKind::Num,
-                    ))
+            TokenKind::Warn => {
+                lexer.next_token();
+                let mut warn = vec![parse_span(lexer)?];
+                while lexer.peek_token().kind == TokenKind::Comma {
+                    lexer.next_token();
+                    warn.push(parse_span(lexer)?);
                 }
-            };
-            if hour > 12 {
-                return Err(ParserError::InvalidNum(lexer.loc(), hour as i32, 0, 12));
+                reminder.set_warn(warn);
             }
-            let minute = match lexer.expect_token(TokenKind::Num) {
-                Ok(token) => token.text.parse::<u32>().unwrap(),
-                Err(token) => {
-                    return Err(ParserError::UnexpectedToken(
-                        token.loc,
-                        token.kind,
-                        token.text,
-                        TokenKind::Num,
-                    ))
+            TokenKind::Urgency => {
+                lexer.next_token();
+                let token = expect(lexer, TokenKind::Str)?;
+                match token.text.parse() {
+                    Ok(urgency) => reminder.set_urgency(urgency),
+                    Err(_) => return Err(ParserError::InvalidUrgency(token.loc, token.text)),
                 }
-            };
-            if minute > 59 {
-                return Err(ParserError::InvalidNum(lexer.loc(), minute as i32, 0, 59));
             }
-            let summary = match lexer.expect_token(TokenKind::Str) {
-                Ok(token) => token.text,
-                Err(token) => match token {
-                    Token {
-                        kind: TokenKind::UnclosedStr,
-                        text,
-                        loc,
-                    } => return Err(ParserError::UnclosedStr(loc, text)),
-                    Token {
-                        kind: TokenKind::End,
-                        ..
-                    } => String::from("No summary provided"),
-                    Token { kind, text, loc } => {
-                        return Err(ParserError::UnexpectedToken(
-                            loc,
-                            kind,
-                            text,
-                            TokenKind::Str,
-                        ))
-                    }
-                },
-            };
-            let description = match lexer.next() {
-                Some(token) => match token {
-                    Token {
-                        kind: TokenKind::Str,
-                        text,
-                        ..
-                    } => Some(text),
-                    Token {
-                        kind: TokenKind::UnclosedStr,
-                        text,
-                        loc,
-                    } => return Err(ParserError::UnclosedStr(loc, text)),
-                    _ => None,
-                },
-                None => None,
-            };
-            Ok(Reminder::new(
-                crate::When::Day(day, hour, minute),
-                summary,
-                description,
-            ))
+            TokenKind::Icon => {
+                lexer.next_token();
+                reminder.set_icon(Some(expect(lexer, TokenKind::Str)?.text));
+            }
+            TokenKind::Timeout => {
+                lexer.next_token();
+                let token = lexer.peek_token();
+                let timeout = if token.kind == TokenKind::Str && token.text == "never" {
+                    lexer.next_token();
+                    0
+                } else {
+                    parse_span(lexer)?
+                };
+                reminder.set_timeout(Some(timeout));
+            }
+            TokenKind::Category => {
+                lexer.next_token();
+                reminder.set_category(Some(expect(lexer, TokenKind::Str)?.text));
+            }
+            TokenKind::From => {
+                lexer.next_token();
+                reminder.set_from(Some(parse_day_from(lexer)?));
+            }
+            _ => return Ok(()),
         }
-        Err(token) => match token {
-            Token {
-                kind: TokenKind::UnclosedStr,
-                text,
-                loc,
-            } => Err(ParserError::UnclosedStr(loc, text)),
-            Token {
-                kind: TokenKind::End,
-                loc,
-                ..
-            } => Err(ParserError::NoToken(loc)),
-            Token { kind, text, loc } => Err(ParserError::UnexpectedToken(
-                loc,
-                kind,
-                text,
-                TokenKind::Str,
-            )),
+    }
+}
+
+// a number and a unit, eg: 15m, in seconds
+pub(crate) fn parse_span<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+) -> Result<u32, ParserError> {
+    let num = expect(lexer, TokenKind::Num)?;
+    let unit = lexer.next_token(); //h lexes as help
+    let text = format!("{}{}", num.text, unit.text);
+    reminder::parse_lead(&text).ok_or(ParserError::InvalidDuration(num.loc, text))
+}
+
+pub fn parse_when<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<When, ParserError> {
+    let token = lexer.next_token();
+    match token.kind {
+        TokenKind::Num => match lexer.peek_token().kind {
+            TokenKind::Colon => {
+                let (hour, minute) = parse_clock_from(lexer, token)?;
+                parse_zone(lexer, When::Time(hour, minute))
+            }
+            TokenKind::Slash => {
+                let when = parse_date_from(lexer, token)?;
+                parse_zone(lexer, when)
+            }
+            _ => Ok(When::Duration(parse_num(&token, 0, i32::MAX)?)),
         },
+        TokenKind::Cron => {
+            let token = expect(lexer, TokenKind::Str)?;
+            let cron = token.text.parse().map_err(|(offset, what)| {
+                let mut loc = token.loc.clone();
+                loc.col += offset + 1; //past the quote
+                ParserError::InvalidCron(loc, what)
+            })?;
+            Ok(When::Cron(cron))
+        }
+        TokenKind::Str => {
+            let day = parse_weekday(token)?;
+            let (hour, minute) = parse_clock(lexer)?;
+            parse_zone(lexer, When::Day(day, hour, minute))
+        }
+        _ => Err(unexpected(token, TokenKind::Num)),
+    }
+}
+
+// an optional tz "Area/City" after a time, day or date
+fn parse_zone<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    when: When,
+) -> Result<When, ParserError> {
+    if lexer.peek_token().kind != TokenKind::Tz {
+        return Ok(when);
+    }
+    lexer.next_token();
+    let token = expect(lexer, TokenKind::Str)?;
+    match token.text.parse() {
+        Ok(tz) => Ok(When::Zoned(Box::new(when), tz)),
+        Err(_) => Err(ParserError::InvalidZone(token.loc, token.text)),
     }
 }
 
+pub fn parse_duration<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+) -> Result<Reminder, ParserError> {
+    let token = expect(lexer, TokenKind::Num)?;
+    let duration = parse_num(&token, 0, i32::MAX)?;
+    let summary = parse_summary(lexer, &Vars::new())?;
+    let body = parse_body(lexer, &Vars::new())?;
+    Ok(Reminder::new(When::Duration(duration), summary, body))
+}
+
+pub fn parse_time<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Reminder, ParserError> {
+    let (hour, minute) = parse_clock(lexer)?;
+    let summary = parse_summary(lexer, &Vars::new())?;
+    let body = parse_body(lexer, &Vars::new())?;
+    Ok(Reminder::new(When::Time(hour, minute), summary, body))
+}
+
+pub fn parse_day<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Reminder, ParserError> {
+    let day = parse_weekday(expect(lexer, TokenKind::Str)?)?;
+    let (hour, minute) = parse_clock(lexer)?;
+    let summary = parse_summary(lexer, &Vars::new())?;
+    let body = parse_body(lexer, &Vars::new())?;
+    Ok(Reminder::new(When::Day(day, hour, minute), summary, body))
+}
+
+pub fn parse_date<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<Reminder, ParserError> {
+    let token = expect(lexer, TokenKind::Num)?;
+    let when = parse_date_from(lexer, token)?;
+    let summary = parse_summary(lexer, &Vars::new())?;
+    let body = parse_body(lexer, &Vars::new())?;
+    Ok(Reminder::new(when, summary, body))
+}
+
+fn parse_weekday(token: Token) -> Result<String, ParserError> {
+    let day = token.text.to_lowercase();
+    if DAYS.contains(&day.as_str()) {
+        Ok(day)
+    } else {
+        Err(ParserError::InvalidDay(token.loc, token.text))
+    }
+}
+
+// hour:minute
+fn parse_clock<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<(u32, u32), ParserError> {
+    let hour = expect(lexer, TokenKind::Num)?;
+    parse_clock_from(lexer, hour)
+}
+
+fn parse_clock_from<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    hour: Token,
+) -> Result<(u32, u32), ParserError> {
+    let hour = parse_num(&hour, 0, 23)?;
+    expect(lexer, TokenKind::Colon)?;
+    let minute = parse_num(&expect(lexer, TokenKind::Num)?, 0, 59)?;
+    Ok((hour, minute))
+}
+
+// day/month/year hour:minute
+fn parse_date_from<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    day: Token,
+) -> Result<When, ParserError> {
+    let day = parse_num(&day, 1, 31)?;
+    expect(lexer, TokenKind::Slash)?;
+    let month = parse_num(&expect(lexer, TokenKind::Num)?, 0, 12)?;
+    expect(lexer, TokenKind::Slash)?;
+    let year = parse_num(&expect(lexer, TokenKind::Num)?, 0, 9999)? as i32;
+    let (hour, minute) = parse_clock(lexer)?;
+    Ok(When::Date(day, month, year, hour, minute))
+}
+
+// day/month/year without a time, it has to be a real day
+fn parse_day_from<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+) -> Result<NaiveDate, ParserError> {
+    let day = expect(lexer, TokenKind::Num)?;
+    let loc = day.loc.clone();
+    let day = parse_num(&day, 1, 31)?;
+    expect(lexer, TokenKind::Slash)?;
+    let month = parse_num(&expect(lexer, TokenKind::Num)?, 1, 12)?;
+    expect(lexer, TokenKind::Slash)?;
+    let year = parse_num(&expect(lexer, TokenKind::Num)?, 1, 9999)? as i32;
+    NaiveDate::from_ymd_opt(year, month, day)
+        .ok_or_else(|| ParserError::InvalidDate(loc, format!("{}/{}/{}", day, month, year)))
+}
+
+fn parse_num(token: &Token, min: i32, max: i32) -> Result<u32, ParserError> {
+    match token.text.parse::<i32>() {
+        Ok(num) if (min..=max).contains(&num) => Ok(num as u32),
+        Ok(num) => Err(ParserError::InvalidNum(token.loc.clone(), num, min, max)),
+        Err(_) => Err(ParserError::InvalidNum(
+            token.loc.clone(),
+            i32::MAX,
+            min,
+            max,
+        )),
+    }
+}
+
+fn parse_summary<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    vars: &Vars,
+) -> Result<String, ParserError> {
+    match lexer.peek_token().kind {
+        TokenKind::End => Ok(String::from("no summary provided")),
+        _ => expand(expect(lexer, TokenKind::Str)?, vars),
+    }
+}
+
+fn parse_body<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    vars: &Vars,
+) -> Result<Option<String>, ParserError> {
+    match lexer.peek_token().kind {
+        TokenKind::Str | TokenKind::UnclosedStr => {
+            Ok(Some(expand(expect(lexer, TokenKind::Str)?, vars)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+// fills in ${name} from vars, builtins are left for when the reminder is shown
+fn expand(token: Token, vars: &Vars) -> Result<String, ParserError> {
+    template::expand(&token.text, |name| match vars.get(name) {
+        Some(value) => Some(value.clone()),
+        None if template::BUILTINS.contains(&name) => Some(format!("${{{}}}", name)),
+        None => None,
+    })
+    .map_err(|(offset, name)| {
+        // offset is in bytes of the text, loc is the column of the opening quote
+        let mut loc = token.loc.clone();
+        loc.col += token.text[..offset].chars().count() + 1;
+        ParserError::UnknownVar(loc, name)
+    })
+}
+
+pub fn expect_end<C: Iterator<Item = char>>(lexer: &mut Lexer<C>) -> Result<(), ParserError> {
+    expect(lexer, TokenKind::End).map(|_| ())
+}
+
+fn expect<C: Iterator<Item = char>>(
+    lexer: &mut Lexer<C>,
+    kind: TokenKind,
+) -> Result<Token, ParserError> {
+    lexer
+        .expect_token(kind.clone())
+        .map_err(|t| unexpected(t, kind))
+}
+
+fn unexpected(token: Token, expected: TokenKind) -> ParserError {
+    match token {
+        Token {
+            kind: TokenKind::UnclosedStr,
+            text,
+            loc,
+        } => ParserError::UnclosedStr(loc, text),
+        Token {
+            kind: TokenKind::End,
+            loc,
+            ..
+        } if expected != TokenKind::End => ParserError::NoToken(loc),
+        Token { kind, text, loc } => ParserError::UnexpectedToken(loc, kind, text, expected),
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum ParserError {
     NoToken(Loc),
     UnexpectedToken(Loc, TokenKind, String, TokenKind), //found, text of token, expected
     UnclosedStr(Loc, String),
     InvalidDay(Loc, String),
-    InvalidNum(Loc, i32, i32, i32), //num found, min, max
+    InvalidNum(Loc, i32, i32, i32),     //num found, min, max
+    IncludeFailed(Loc, String, String), //path, reason
+    IncludeCycle(Loc, String),
+    ReadFailed(Loc, String), //reason
+    UnknownVar(Loc, String),
+    InvalidCron(Loc, String),
+    InvalidZone(Loc, String),
+    InvalidDuration(Loc, String),
+    InvalidUrgency(Loc, String),
+    InvalidPriority(Loc, String),
+    InvalidQuiet(Loc, String),
+    InvalidDate(Loc, String),
+    Unsupported(Loc, String), //something an importer cant turn into a reminder
+}
+
+impl ParserError {
+    pub fn loc(&self) -> &Loc {
+        match self {
+            ParserError::NoToken(loc)
+            | ParserError::UnexpectedToken(loc, ..)
+            | ParserError::UnclosedStr(loc, _)
+            | ParserError::InvalidDay(loc, _)
+            | ParserError::InvalidNum(loc, ..)
+            | ParserError::IncludeFailed(loc, ..)
+            | ParserError::IncludeCycle(loc, _)
+            | ParserError::ReadFailed(loc, _)
+            | ParserError::UnknownVar(loc, _)
+            | ParserError::InvalidCron(loc, _)
+            | ParserError::InvalidZone(loc, _)
+            | ParserError::InvalidDuration(loc, _)
+            | ParserError::InvalidUrgency(loc, _)
+            | ParserError::InvalidPriority(loc, _)
+            | ParserError::InvalidQuiet(loc, _)
+            | ParserError::InvalidDate(loc, _)
+            | ParserError::Unsupported(loc, _) => loc,
+        }
+    }
 }
 
+impl Display for ParserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParserError::NoToken(loc) => write!(f, "{}: expected input", loc),
+            ParserError::UnexpectedToken(loc, got, text, expected) => write!(
+                f,
+                "{}: unexpected token {:?} '{}', expected {:?}",
+                loc, got, text, expected
+            ),
+            ParserError::UnclosedStr(loc, text) => {
+                write!(f, "{}: unclosed string '{}'", loc, text)
+            }
+            ParserError::InvalidDay(loc, day) => write!(
+                f,
+                "{}: invalid day '{}', only shorthand works right now, eg: mon/tue/wed/thu/fri/sat/sun",
+                loc, day
+            ),
+            ParserError::InvalidNum(loc, num, min, max) => {
+                write!(f, "{}: {} is not between {}-{}", loc, num, min, max)
+            }
+            ParserError::IncludeFailed(loc, path, reason) => {
+                write!(f, "{}: could not include '{}': {}", loc, path, reason)
+            }
+            ParserError::IncludeCycle(loc, path) => {
+                write!(f, "{}: including '{}' would form a cycle", loc, path)
+            }
+            ParserError::ReadFailed(loc, reason) => {
+                write!(f, "{}: could not read file: {}", loc, reason)
+            }
+            ParserError::UnknownVar(loc, name) => {
+                write!(f, "{}: unknown variable '{}'", loc, name)
+            }
+            ParserError::InvalidCron(loc, what) => {
+                write!(f, "{}: invalid cron expression, {}", loc, what)
+            }
+            ParserError::InvalidZone(loc, tz) => {
+                write!(f, "{}: unknown time zone '{}', expected a name like Europe/London", loc, tz)
+            }
+            ParserError::InvalidDuration(loc, text) => write!(
+                f,
+                "{}: invalid duration '{}', expected a number of s/m/h/d/w eg: 15m",
+                loc, text
+            ),
+            ParserError::InvalidUrgency(loc, urgency) => write!(
+                f,
+                "{}: invalid urgency '{}', expected low/normal/critical",
+                loc, urgency
+            ),
+            ParserError::InvalidPriority(loc, priority) => write!(
+                f,
+                "{}: invalid priority '{}', expected !low/!normal/!high",
+                loc, priority
+            ),
+            ParserError::InvalidQuiet(loc, word) => write!(
+                f,
+                "{}: unknown quiet option '{}', expected defer/drop/silent or tag \"name\"",
+                loc, word
+            ),
+            ParserError::InvalidDate(loc, date) => {
+                write!(f, "{}: invalid date '{}', that day doesnt exist", loc, date)
+            }
+            ParserError::Unsupported(loc, what) => write!(f, "{}: unsupported {}", loc, what),
+        }
+    }
+}
+
+impl std::error::Error for ParserError {}
+
 pub enum Command {
     Quit,
     Remind,
     List,
     Edit,
+    Delete,
+    Done,
+    Snooze,
+    Undo,
+    Redo,
     Help,
     Invalid(Option<Token>),
 }
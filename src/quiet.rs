@@ -0,0 +1,61 @@
+use chrono::{DateTime, Datelike, Local, Timelike, Weekday};
+
+use crate::Reminder;
+
+// quiet hours, set in a reminder file with
+//   quiet [days] <from> <to> [defer|drop|silent] [tag "name"]
+//   urgent "tag"
+// a window that ends at or before it starts runs past midnight, so 0:00 0:00 is the whole day
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Defer,  //go off once the window is over
+    Drop,   //dont go off at all this time
+    Silent, //go off at low urgency
+}
+
+#[derive(Debug, Clone)]
+pub struct Window {
+    pub days: Vec<Weekday>, //days the window starts on, empty is every day
+    pub from: (u32, u32),
+    pub to: (u32, u32),
+    pub action: Action,
+    pub tag: Option<String>, //only reminders with this tag, None is all of them
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Quiet {
+    pub windows: Vec<Window>,
+    pub urgent: Vec<String>, //tags that ignore quiet hours
+}
+
+impl Quiet {
+    // what to do with a reminder that comes due at `now`, None if it can go off as usual.
+    // the first window that applies wins
+    pub fn action(&self, reminder: &Reminder, now: DateTime<Local>) -> Option<Action> {
+        if self.urgent.iter().any(|t| reminder.has_tag(t)) {
+            return None;
+        }
+        self.windows
+            .iter()
+            .filter(|w| match &w.tag {
+                Some(tag) => reminder.has_tag(tag),
+                None => true,
+            })
+            .find(|w| w.contains(now))
+            .map(|w| w.action)
+    }
+}
+
+impl Window {
+    pub fn contains(&self, now: DateTime<Local>) -> bool {
+        let time = (now.hour(), now.minute());
+        let today = now.weekday();
+        let starts_on = |day: Weekday| self.days.is_empty() || self.days.contains(&day);
+        if self.from < self.to {
+            starts_on(today) && self.from <= time && time < self.to
+        } else {
+            (starts_on(today) && self.from <= time) || (starts_on(today.pred()) && time < self.to)
+        }
+    }
+}
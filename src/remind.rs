@@ -0,0 +1,335 @@
+use chrono::{Datelike, NaiveDate, NaiveTime, Timelike, Weekday};
+
+use crate::{
+    lexer::Loc,
+    parser::{Parsed, ParserError, DAYS},
+    Reminder, When,
+};
+
+// the REM lines of a classic remind(1) file. a date spec made of a day, month, year or
+// weekdays, AT for the time (9:00 without one), +n to warn on each of the n days before,
+// *n to repeat daily or weekly from a date on, TAG and MSG. everything else is reported
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+#[derive(Default)]
+struct Rem {
+    day: Option<u32>,
+    month: Option<u32>,
+    year: Option<i32>,
+    weekdays: Vec<Weekday>,
+    at: Option<(u32, u32)>,
+    delta: u32,
+    repeat: Option<u32>,
+    tags: Vec<String>,
+}
+
+pub fn import(text: &str, file_path: Option<String>) -> Parsed {
+    let mut parsed = Parsed::default();
+    for (lnum, line) in lines(text) {
+        let loc = |col: usize| Loc {
+            file_path: file_path.clone(),
+            row: lnum + 1,
+            col,
+        };
+        let mut words = words(&line);
+        let (col, command) = match words.peek() {
+            Some((col, word)) => (*col, *word),
+            None => continue,
+        };
+        if command.starts_with('#') || command.starts_with(';') {
+            continue;
+        }
+        if !command.eq_ignore_ascii_case("rem") {
+            parsed
+                .errors
+                .push(unsupported(loc(col), "command", command));
+            continue;
+        }
+        words.next();
+
+        match parse_rem(&line, words, &loc) {
+            Ok(reminders) => parsed.reminders.extend(reminders),
+            Err(e) => parsed.errors.push(e),
+        }
+    }
+    parsed
+}
+
+fn parse_rem<'a>(
+    line: &'a str,
+    mut words: impl Iterator<Item = (usize, &'a str)>,
+    loc: &impl Fn(usize) -> Loc,
+) -> Result<Vec<Reminder>, ParserError> {
+    let mut rem = Rem::default();
+    let mut msg = None;
+    while let Some((col, word)) = words.next() {
+        let lower = word.to_lowercase();
+        match lower.as_str() {
+            "msg" | "msf" => {
+                // the message is the rest of the line as it was written
+                let start = line
+                    .char_indices()
+                    .nth(col - 1 + word.chars().count())
+                    .map_or(line.len(), |(i, _)| i);
+                msg = Some((
+                    col,
+                    message(&line[start..], col + word.chars().count(), loc)?,
+                ));
+                break;
+            }
+            "at" => {
+                let (col, time) = words.next().ok_or_else(|| no_value(loc(col), word))?;
+                let time = parse_time(time).ok_or_else(|| unsupported(loc(col), "time", time))?;
+                rem.at = Some(time);
+            }
+            "tag" => {
+                let (_, tag) = words.next().ok_or_else(|| no_value(loc(col), word))?;
+                rem.tags.push(tag.to_string());
+            }
+            "once" => {} //we never go back over missed reminders anyway
+            _ if word.starts_with('+') => {
+                if rem.at.is_some() {
+                    return Err(unsupported(loc(col), "time delta", word));
+                }
+                rem.delta = parse_num(word.trim_start_matches('+'))
+                    .ok_or_else(|| unsupported(loc(col), "advance warning", word))?;
+            }
+            _ if word.starts_with('*') => {
+                if rem.at.is_some() {
+                    return Err(unsupported(loc(col), "time repeat", word));
+                }
+                rem.repeat = Some(
+                    parse_num(&word[1..])
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| unsupported(loc(col), "repeat", word))?,
+                );
+            }
+            _ => parse_date_part(&mut rem, &lower)
+                .map_err(|what| ParserError::Unsupported(loc(col), what))?,
+        }
+    }
+
+    let (msg_col, msg) = match msg {
+        Some(msg) => msg,
+        None => {
+            return Err(ParserError::Unsupported(
+                loc(1),
+                String::from("REM without a MSG"),
+            ))
+        }
+    };
+    if msg.is_empty() {
+        return Err(ParserError::Unsupported(
+            loc(msg_col),
+            String::from("empty MSG"),
+        ));
+    }
+
+    let (h, m) = rem.at.unwrap_or((9, 0));
+    let (whens, from) = base(&rem, h, m).map_err(|what| ParserError::Unsupported(loc(1), what))?;
+    Ok(whens
+        .into_iter()
+        .map(|when| {
+            let mut reminder = Reminder::new(when, msg.clone(), None);
+            reminder.set_tags(rem.tags.clone());
+            reminder.set_warn((1..=rem.delta).map(|n| n * 24 * 60 * 60).collect());
+            reminder.set_from(from);
+            reminder
+        })
+        .collect())
+}
+
+// a day, month, year, weekday or an iso date
+fn parse_date_part(rem: &mut Rem, word: &str) -> Result<(), String> {
+    let twice = |what: &str| Err(format!("{} given twice", what));
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        if rem.day.is_some() || rem.month.is_some() || rem.year.is_some() {
+            return twice("date");
+        }
+        rem.day = Some(date.day());
+        rem.month = Some(date.month());
+        rem.year = Some(date.year());
+    } else if let Some(n) = parse_num(word) {
+        match n {
+            1..=31 if rem.day.is_some() => return twice("day"),
+            1..=31 => rem.day = Some(n),
+            1990..=9999 if rem.year.is_some() => return twice("year"),
+            1990..=9999 => rem.year = Some(n as i32),
+            _ => return Err(format!("number '{}', expected a day or a year", word)),
+        }
+    } else if let Some(month) = find(&MONTHS, word) {
+        if rem.month.is_some() {
+            return twice("month");
+        }
+        rem.month = Some(month as u32 + 1);
+    } else if let Some(day) = find(&WEEKDAYS, word) {
+        rem.weekdays.push(DAYS[day].parse().unwrap());
+    } else {
+        return Err(format!("REM clause '{}'", word.to_uppercase()));
+    }
+    Ok(())
+}
+
+// what the date spec turns into without any advance warning, and the day a repeat starts on
+fn base(rem: &Rem, h: u32, m: u32) -> Result<(Vec<When>, Option<NaiveDate>), String> {
+    let date = match (rem.day, rem.month, rem.year) {
+        (Some(d), Some(mon), Some(y)) => Some(
+            NaiveDate::from_ymd_opt(y, mon, d)
+                .ok_or_else(|| format!("date {}/{}/{}, it doesnt exist", d, mon, y))?,
+        ),
+        _ => None,
+    };
+
+    if let Some(n) = rem.repeat {
+        let date = match date {
+            Some(date) if rem.weekdays.is_empty() => date,
+            _ => return Err(String::from("repeat without a full date")),
+        };
+        return match n {
+            1 => Ok((vec![When::Time(h, m)], Some(date))),
+            7 => Ok((vec![When::Day(weekday(date.weekday()), h, m)], Some(date))),
+            n => Err(format!("repeat every {} days, only *1 and *7 are", n)),
+        };
+    }
+
+    let whens = match (rem.day, &rem.weekdays[..]) {
+        (Some(_), [_, ..]) => Err(String::from("weekday together with a date")),
+        (Some(d), []) => Ok(vec![When::Date(
+            d,
+            rem.month.unwrap_or(0),
+            rem.year.unwrap_or(0),
+            h,
+            m,
+        )]),
+        (None, _) if rem.month.is_some() || rem.year.is_some() => {
+            Err(String::from("month or year without a day"))
+        }
+        (None, []) => Ok(vec![When::Time(h, m)]),
+        (None, days) => Ok(days.iter().map(|d| When::Day(weekday(*d), h, m)).collect()),
+    };
+    Ok((whens?, None))
+}
+
+// %" marks calendar text, %% is a % and %_ a new line, the other substitutions have nothing to become
+fn message(text: &str, col: usize, loc: &impl Fn(usize) -> Loc) -> Result<String, ParserError> {
+    let mut out = String::new();
+    let mut chars = text.chars().enumerate();
+    while let Some((i, c)) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some((_, '"')) => {}
+            Some((_, '%')) => out.push('%'),
+            Some((_, '_')) => out.push('\n'),
+            Some((_, c)) => {
+                return Err(unsupported(
+                    loc(col + i),
+                    "substitution",
+                    &format!("%{}", c),
+                ))
+            }
+            None => out.push('%'),
+        }
+    }
+    Ok(out.trim().to_string())
+}
+
+// H:MM, H.MM, with am or pm
+fn parse_time(word: &str) -> Option<(u32, u32)> {
+    let lower = word.to_lowercase();
+    let (time, offset) = match (lower.strip_suffix("am"), lower.strip_suffix("pm")) {
+        (Some(time), _) => (time, Some(0)),
+        (_, Some(time)) => (time, Some(12)),
+        _ => (lower.as_str(), None),
+    };
+    let time = NaiveTime::parse_from_str(&time.replace('.', ":"), "%H:%M").ok()?;
+    match offset {
+        Some(offset) if (1..=12).contains(&time.hour()) => {
+            Some((time.hour() % 12 + offset, time.minute()))
+        }
+        Some(_) => None,
+        None => Some((time.hour(), time.minute())),
+    }
+}
+
+fn parse_num(word: &str) -> Option<u32> {
+    word.parse().ok()
+}
+
+// the index of the name that word is an abbreviation of, at least 3 letters long
+fn find(names: &[&str], word: &str) -> Option<usize> {
+    if word.len() < 3 {
+        return None;
+    }
+    names.iter().position(|name| name.starts_with(word))
+}
+
+fn weekday(day: Weekday) -> String {
+    DAYS[day.num_days_from_monday() as usize].to_string()
+}
+
+// lines along with the zero based line they start on, a trailing \ continues a line
+fn lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = vec![];
+    let mut continued = false;
+    for (lnum, line) in text.lines().enumerate() {
+        let (line, next) = match line.strip_suffix('\\') {
+            Some(line) => (line.trim_end(), true),
+            None => (line, false),
+        };
+        match lines.last_mut() {
+            Some((_, last)) if continued => {
+                last.push(' ');
+                last.push_str(line.trim_start());
+            }
+            _ => lines.push((lnum, line.to_string())),
+        }
+        continued = next;
+    }
+    lines
+}
+
+// every word along with the column it starts at
+fn words(line: &str) -> std::iter::Peekable<impl Iterator<Item = (usize, &str)>> {
+    line.split([' ', '\t'])
+        .scan(1, |col, word| {
+            let start = *col;
+            *col += word.chars().count() + 1;
+            Some((start, word))
+        })
+        .filter(|(_, word)| !word.is_empty())
+        .peekable()
+}
+
+fn no_value(loc: Loc, word: &str) -> ParserError {
+    ParserError::Unsupported(loc, format!("{} without a value", word))
+}
+
+fn unsupported(loc: Loc, what: &str, text: &str) -> ParserError {
+    ParserError::Unsupported(loc, format!("{} '{}'", what, text))
+}
@@ -1,21 +1,47 @@
-use chrono::{DateTime, Datelike, Local, Timelike};
-use libnotify::Notification;
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    cron::Cron,
+    links,
+    notify::{Backend, Notice, Urgency},
+    parser::ParserError,
+    quiet::Action,
+    template,
+};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, TimeZone, Weekday};
+use chrono_tz::Tz;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reminder {
     summary: String,
     body: Option<String>,
     when: When,
+    tags: Vec<String>, //with their + or @
+    priority: Priority,
+    warn: Vec<u32>, //lead times in seconds, longest first
+    urgency: Urgency,
+    icon: Option<String>,
+    timeout: Option<u32>, //seconds the notification stays up, 0 is until dismissed
+    category: Option<String>,
+    from: Option<NaiveDate>, //doesnt go off before this day
     done: bool,
-    notif: Option<Notification>,
+    count: u32, //times shown
+    created: DateTime<Local>,
+    last: Option<DateTime<Local>>,    //last time shown
+    warned: Option<DateTime<Local>>,  //last time a lead time was shown
+    snoozed: Option<DateTime<Local>>, //goes off then instead of when it would have
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum When {
     Duration(u32),
     Day(String, u32, u32),
     Date(u32, u32, i32, u32, u32),
     Time(u32, u32),
+    Cron(Cron),
+    Zoned(Box<When>, Tz), //a time, day or date in a time zone other than local
 }
 
 impl Reminder {
@@ -24,81 +50,626 @@ impl Reminder {
             when,
             summary,
             body,
-            notif: None,
+            tags: vec![],
+            priority: Priority::Normal,
+            warn: vec![],
+            urgency: Urgency::Normal,
+            icon: None,
+            timeout: None,
+            category: None,
+            from: None,
             done: false,
+            count: 0,
+            created: Local::now(),
+            last: None,
+            warned: None,
+            snoozed: None,
         }
     }
 
     pub fn check(&mut self, now: DateTime<Local>) -> bool {
-        use When::*;
-        match self.when.clone() {
-            Duration(s) => {
-                if s > 0 {
-                    self.when = When::Duration(s - 1);
-                    false
-                } else {
-                    true
-                }
+        if let Some(until) = self.snoozed {
+            return now >= until;
+        }
+        match self.next_fire_time(self.last.unwrap_or(self.created)) {
+            Some(time) => now >= time,
+            None => {
+                self.done = true;
+                false
             }
-            Day(d, h, m) => {
-                if d.to_lowercase() != now.weekday().to_string().to_lowercase() {
-                    return false;
-                }
-                if now.hour() < h {
-                    return false;
-                }
-                if now.minute() < m {
-                    return false;
-                }
-                true
+        }
+    }
+
+    // the lead time to warn about now, if any. a lead time that had already passed when the
+    // reminder was made or last went off is skipped, when several are due only the shortest is shown
+    pub fn check_warn(&self, now: DateTime<Local>) -> Option<u32> {
+        if self.snoozed.is_some() {
+            return None;
+        }
+        let start = self.last.unwrap_or(self.created);
+        let time = self.next_fire_time(start).filter(|t| now < *t)?;
+        self.warn
+            .iter()
+            .rev()
+            .map(|lead| (*lead, time - ChronoDuration::seconds(*lead as i64)))
+            .find(|(_, at)| *at <= now)
+            .filter(|(_, at)| *at > start && self.warned.is_none_or(|w| *at > w))
+            .map(|(lead, _)| lead)
+    }
+
+    // durations count from when the reminder was made, everything else is when.next_fire_time
+    // from the start of the from day on
+    pub fn next_fire_time(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.when {
+            When::Duration(s) => {
+                Some(self.created + ChronoDuration::seconds(s as i64)).filter(|t| *t > after)
             }
-            Date(d, m, y, h, min) => {
-                if now.year() < y && y != 0 {
-                    return false;
-                }
-                if now.month() < m && m != 0 {
-                    return false;
-                }
-                if now.day() < d {
-                    return false;
-                }
-                if now.hour() < h {
-                    return false;
-                }
-                if now.minute() < min {
-                    return false;
-                }
-                true
+            _ => {
+                let start = self.from.and_then(|day| at(&Local, day, 0, 0));
+                let after = match start {
+                    Some(start) if start > after => start - ChronoDuration::seconds(1),
+                    _ => after,
+                };
+                self.when.next_fire_time(after)
             }
-            Time(h, m) => {
-                if now.hour() < h {
-                    return false;
-                }
-                if now.minute() < m {
-                    return false;
+        }
+    }
+
+    // the next time this goes off, a one off date that already passed is still due then
+    pub fn due(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        if self.snoozed.is_some() {
+            return self.snoozed;
+        }
+        let (when, tz) = match &self.when {
+            When::Zoned(when, tz) => (when.as_ref(), Some(tz)),
+            when => (when, None),
+        };
+        match *when {
+            When::Date(d, mon, y, h, m) if mon != 0 && y != 0 => {
+                let date = NaiveDate::from_ymd_opt(y, mon, d)?;
+                match tz {
+                    Some(tz) => at(tz, date, h, m),
+                    None => at(&Local, date, h, m),
                 }
-                true
             }
+            _ => self.next_fire_time(now),
         }
     }
 
-    pub fn show(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        if self.notif.is_none() {
-            Notification::new(&self.summary, None, None);
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+
+    // a tag without a + or @ is taken to be a +project
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        self.tags.clear();
+        for tag in tags {
+            let tag = if tag.starts_with(['+', '@']) {
+                tag
+            } else {
+                format!("+{}", tag)
+            };
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+    }
+
+    // `tag` with its + or @ has to match exactly, without one either will do
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags
+            .iter()
+            .any(|t| t == tag || (!tag.starts_with(['+', '@']) && t[1..] == *tag))
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    pub fn warn(&self) -> &[u32] {
+        &self.warn
+    }
+
+    pub fn set_warn(&mut self, mut warn: Vec<u32>) {
+        warn.sort_unstable_by(|a, b| b.cmp(a));
+        warn.dedup();
+        self.warn = warn;
+    }
+
+    pub fn urgency(&self) -> Urgency {
+        self.urgency
+    }
+
+    pub fn set_urgency(&mut self, urgency: Urgency) {
+        self.urgency = urgency;
+    }
+
+    pub fn icon(&self) -> Option<&str> {
+        self.icon.as_deref()
+    }
+
+    pub fn set_icon(&mut self, icon: Option<String>) {
+        self.icon = icon;
+    }
+
+    pub fn timeout(&self) -> Option<u32> {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<u32>) {
+        self.timeout = timeout;
+    }
+
+    pub fn category(&self) -> Option<&str> {
+        self.category.as_deref()
+    }
+
+    pub fn set_category(&mut self, category: Option<String>) {
+        self.category = category;
+    }
+
+    pub fn from(&self) -> Option<NaiveDate> {
+        self.from
+    }
+
+    pub fn set_from(&mut self, from: Option<NaiveDate>) {
+        self.from = from;
+    }
+
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn body(&self) -> Option<&str> {
+        self.body.as_deref()
+    }
+
+    pub fn when(&self) -> &When {
+        &self.when
+    }
+
+    pub fn show(&mut self, backend: &mut dyn Backend) -> Result<(), ReminderError> {
+        self.deliver(backend, None, None, None)
+    }
+
+    // shown `lead` seconds before the reminder goes off, it doesnt count as being shown
+    pub fn show_warning(
+        &mut self,
+        backend: &mut dyn Backend,
+        lead: u32,
+    ) -> Result<(), ReminderError> {
+        self.deliver(backend, None, Some(lead), None)
+    }
+
+    // shows the reminder, or the warning for `lead`, as the quiet hours say to. id is the
+    // store's, if it came from the store
+    pub fn deliver(
+        &mut self,
+        backend: &mut dyn Backend,
+        id: Option<u32>,
+        lead: Option<u32>,
+        quiet: Option<Action>,
+    ) -> Result<(), ReminderError> {
+        let now = Local::now();
+        let count = self.count + 1;
+        match quiet {
+            Some(Action::Defer) => return Ok(()),
+            Some(Action::Drop) => {}
+            Some(Action::Silent) => {
+                let mut notice = self.notice(lead, now, count);
+                notice.urgency = Urgency::Low;
+                notice.id = id;
+                backend
+                    .show(&self.key(), &notice)
+                    .map_err(ReminderError::Notify)?;
+            }
+            None => {
+                let mut notice = self.notice(lead, now, count);
+                notice.id = id;
+                backend
+                    .show(&self.key(), &notice)
+                    .map_err(ReminderError::Notify)?;
+            }
+        }
+
+        if lead.is_some() {
+            self.warned = Some(now);
+            return Ok(());
+        }
+        if quiet != Some(Action::Drop) {
+            self.count = count;
+        }
+        self.last = Some(now);
+        self.snoozed = None;
+        self.done = self.next_fire_time(now).is_none();
+        Ok(())
+    }
+
+    // what the backend is given when the reminder goes off for the `count`th time
+    pub fn notice(&self, lead: Option<u32>, now: DateTime<Local>, count: u32) -> Notice {
+        let expand = |text: &str| {
+            template::expand(text, |name| template::builtin(name, now, count))
+                .unwrap_or_else(|_| text.to_string())
+        };
+        let summary = match lead {
+            Some(lead) => format!("in {}: {}", lead_text(lead), expand(&self.summary)),
+            None => expand(&self.summary),
+        };
+        let body = self.body.as_deref().map(expand);
+        let urls = body
+            .as_deref()
+            .map(links::find)
+            .unwrap_or_default()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        // an urgency that was set wins over the priority
+        let urgency = match self.urgency {
+            Urgency::Normal => self.priority.urgency(),
+            urgency => urgency,
+        };
+        Notice {
+            summary,
+            body,
+            urgency,
+            icon: self.icon.clone(),
+            timeout: self.timeout,
+            category: self.category.clone(),
+            urls,
+            id: None,
         }
-        println!("{:?}", self);
-        self.done = true;
-        Ok(self.notif.as_ref().unwrap().show()?)
     }
 
     pub fn is_done(&self) -> bool {
         self.done
     }
+
+    // done for good, it wont go off again
+    pub fn set_done(&mut self) {
+        self.done = true;
+        self.snoozed = None;
+    }
+
+    // goes off at `until` instead, even if it was done
+    pub fn snooze(&mut self, until: DateTime<Local>) {
+        self.snoozed = Some(until);
+        self.done = false;
+    }
+
+    pub fn snoozed(&self) -> Option<DateTime<Local>> {
+        self.snoozed
+    }
+
+    pub fn warned(&self) -> Option<DateTime<Local>> {
+        self.warned
+    }
+
+    pub fn created(&self) -> DateTime<Local> {
+        self.created
+    }
+
+    pub fn last(&self) -> Option<DateTime<Local>> {
+        self.last
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // puts back the state of a reminder that was saved, see store
+    pub fn restore(
+        &mut self,
+        created: DateTime<Local>,
+        last: Option<DateTime<Local>>,
+        snoozed: Option<DateTime<Local>>,
+        warned: Option<DateTime<Local>>,
+        count: u32,
+        done: bool,
+    ) {
+        self.created = created;
+        self.last = last;
+        self.snoozed = snoozed;
+        self.warned = warned;
+        self.count = count;
+        self.done = done;
+    }
+
+    // identifies the reminder across reloads, two reminders are the same if they read the same
+    pub fn key(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl When {
+    // the first time after `after` this goes off, or None if it never will again.
+    // a Duration has nothing to count from so it counts from `after`
+    pub fn next_fire_time(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        self.next_in(&Local, after)
+    }
+
+    // the zone is the one the hours and days are read in
+    fn next_in<Z: TimeZone>(&self, tz: &Z, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.with_timezone(tz).naive_local().date();
+        match self {
+            When::Duration(s) => Some(after + ChronoDuration::seconds(*s as i64)),
+            When::Time(h, m) => (0..=1)
+                .map(|i| start + ChronoDuration::days(i))
+                .find_map(|date| at(tz, date, *h, *m).filter(|t| *t > after)),
+            When::Day(d, h, m) => {
+                let day = d.parse::<Weekday>().ok()?;
+                (0..=7)
+                    .map(|i| start + ChronoDuration::days(i))
+                    .filter(|date| date.weekday() == day)
+                    .find_map(|date| at(tz, date, *h, *m).filter(|t| *t > after))
+            }
+            When::Date(d, mon, y, h, m) if *mon != 0 && *y != 0 => {
+                NaiveDate::from_ymd_opt(*y, *mon, *d)
+                    .and_then(|date| at(tz, date, *h, *m))
+                    .filter(|t| *t > after)
+            }
+            When::Date(d, mon, y, h, m) => {
+                // walk month by month, a year or month of 0 matches any
+                let mut year = start.year();
+                let mut month = start.month();
+                for _ in 0..12 * 8 {
+                    if *y != 0 && year > *y {
+                        return None;
+                    }
+                    if (*y == 0 || year == *y) && (*mon == 0 || month == *mon) {
+                        let time = NaiveDate::from_ymd_opt(year, month, *d)
+                            .and_then(|date| at(tz, date, *h, *m))
+                            .filter(|t| *t > after);
+                        if time.is_some() {
+                            return time;
+                        }
+                    }
+                    month += 1;
+                    if month > 12 {
+                        month = 1;
+                        year += 1;
+                    }
+                }
+                None
+            }
+            When::Cron(cron) => cron.next_fire_time(after),
+            When::Zoned(when, tz) => when.next_in(tz, after),
+        }
+    }
+
+    pub fn tz(&self) -> Option<Tz> {
+        match self {
+            When::Zoned(_, tz) => Some(*tz),
+            _ => None,
+        }
+    }
+}
+
+// hour:minute on date in the zone. a time that is repeated when the clocks go back is the
+// first of the two, one skipped when they go forward is an hour later
+pub(crate) fn at<Z: TimeZone>(
+    tz: &Z,
+    date: NaiveDate,
+    hour: u32,
+    minute: u32,
+) -> Option<DateTime<Local>> {
+    let time = date.and_hms_opt(hour, minute, 0)?;
+    tz.from_local_datetime(&time)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(time + ChronoDuration::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Local))
+}
+
+impl Display for Reminder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "remind {} {}", self.when, quote(&self.summary))?;
+        if let Some(body) = &self.body {
+            write!(f, " {}", quote(body))?;
+        }
+        if self.priority != Priority::Normal {
+            write!(f, " !{}", self.priority)?;
+        }
+        for tag in &self.tags {
+            write!(f, " {}", tag)?;
+        }
+        if self.urgency != Urgency::Normal {
+            write!(f, " urgency {}", self.urgency)?;
+        }
+        if let Some(icon) = &self.icon {
+            write!(f, " icon {}", quote(icon))?;
+        }
+        match self.timeout {
+            Some(0) => write!(f, " timeout never")?,
+            Some(timeout) => write!(f, " timeout {}", lead_short(timeout))?,
+            None => {}
+        }
+        if let Some(category) = &self.category {
+            write!(f, " category {}", quote(category))?;
+        }
+        if let Some(from) = self.from {
+            write!(f, " from {}/{}/{}", from.day(), from.month(), from.year())?;
+        }
+        if !self.warn.is_empty() {
+            let warn: Vec<String> = self.warn.iter().map(|lead| lead_short(*lead)).collect();
+            write!(f, " warn {}", warn.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for When {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            When::Duration(s) => write!(f, "{}", s),
+            When::Day(d, h, m) => write!(f, "{} {}:{:02}", d, h, m),
+            When::Date(d, mon, y, h, m) => write!(f, "{}/{}/{} {}:{:02}", d, mon, y, h, m),
+            When::Time(h, m) => write!(f, "{}:{:02}", h, m),
+            When::Cron(cron) => write!(f, "cron \"{}\"", cron),
+            When::Zoned(when, tz) => write!(f, "{} tz \"{}\"", when, tz),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Priority {
+    Low,
+    #[default]
+    Normal,
+    High,
+}
+
+impl Priority {
+    // what the notification is shown with unless an urgency was set
+    pub fn urgency(self) -> Urgency {
+        match self {
+            Priority::Low => Urgency::Low,
+            Priority::Normal => Urgency::Normal,
+            Priority::High => Urgency::Critical,
+        }
+    }
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Priority::Low => write!(f, "low"),
+            Priority::Normal => write!(f, "normal"),
+            Priority::High => write!(f, "high"),
+        }
+    }
+}
+
+impl FromStr for Priority {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            _ => Err(()),
+        }
+    }
+}
+
+// suffix, seconds and name of the units a lead time can be given in
+const UNITS: [(&str, u32, &str); 5] = [
+    ("w", 7 * 24 * 60 * 60, "week"),
+    ("d", 24 * 60 * 60, "day"),
+    ("h", 60 * 60, "hour"),
+    ("m", 60, "minute"),
+    ("s", 1, "second"),
+];
+
+// a lead time like 15m or 1d in seconds
+pub fn parse_lead(text: &str) -> Option<u32> {
+    let split = text.find(|c: char| !c.is_ascii_digit())?;
+    let (num, unit) = text.split_at(split);
+    let (_, seconds, _) = UNITS.iter().find(|(suffix, ..)| *suffix == unit)?;
+    num.parse::<u32>()
+        .ok()?
+        .checked_mul(*seconds)
+        .filter(|lead| *lead > 0)
+}
+
+// the lead time in the largest unit it is a whole number of
+fn lead_unit(lead: u32) -> (u32, &'static str, &'static str) {
+    UNITS
+        .iter()
+        .find(|(_, seconds, _)| lead.is_multiple_of(*seconds))
+        .map(|(suffix, seconds, name)| (lead / seconds, *suffix, *name))
+        .unwrap_or((lead, "s", "second"))
+}
+
+fn lead_short(lead: u32) -> String {
+    let (n, suffix, _) = lead_unit(lead);
+    format!("{}{}", n, suffix)
+}
+
+// 15 minutes, 1 day
+pub fn lead_text(lead: u32) -> String {
+    let (n, _, name) = lead_unit(lead);
+    format!("{} {}{}", n, name, if n == 1 { "" } else { "s" })
+}
+
+// text as a string the lexer reads back as it was. quoted with whichever quote isnt in it,
+// text with both has its " escaped, as does a backslash that would read as an escape.
+// a statement is a single line so newlines are written as \n
+pub fn quote(text: &str) -> String {
+    let text = text.replace('\r', "");
+    let quote = if text.contains('"') && !text.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+    let mut out = String::from(quote);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let next = chars.peek();
+        if c == '\n' {
+            out.push_str("\\n");
+            continue;
+        }
+        if c == quote || (c == '\\' && next.is_none_or(|n| [quote, '\\', 'n'].contains(n))) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push(quote);
+    out
+}
+
+// the reloaded set of reminders, keeping the live reminder (and its state) for every one that didnt change
+pub fn merge(live: Vec<Reminder>, reloaded: Vec<Reminder>) -> Vec<Reminder> {
+    let mut live: Vec<(String, Option<Reminder>)> =
+        live.into_iter().map(|r| (r.key(), Some(r))).collect();
+    reloaded
+        .into_iter()
+        .map(|r| {
+            let key = r.key();
+            live.iter_mut()
+                .find(|(k, l)| *k == key && l.is_some())
+                .and_then(|(_, l)| l.take())
+                .unwrap_or(r)
+        })
+        .collect()
 }
 
 #[derive(Debug)]
-pub enum ReminderError<'a> {
-    FromStr(&'a str),
+pub enum ReminderError {
+    Parse(ParserError),
+    Notify(String),
+}
+
+impl Display for ReminderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReminderError::Parse(e) => write!(f, "{}", e),
+            ReminderError::Notify(msg) => write!(f, "failed to show notification: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ReminderError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReminderError::Parse(e) => Some(e),
+            ReminderError::Notify(_) => None,
+        }
+    }
+}
+
+impl From<ParserError> for ReminderError {
+    fn from(e: ParserError) -> Self {
+        ReminderError::Parse(e)
+    }
 }
 
 //impl<'a> FromStr for Reminder<'a> {
@@ -132,6 +703,20 @@ pub enum ReminderError<'a> {
 
 #[macro_export]
 macro_rules! remind {
+    (cron $expr:literal $sum:literal) => {
+        Reminder::new(
+            $crate::When::Cron($expr.parse().expect("invalid cron expression")),
+            String::from($sum),
+            None,
+        )
+    };
+    (cron $expr:literal $sum:literal $body:literal) => {
+        Reminder::new(
+            $crate::When::Cron($expr.parse().expect("invalid cron expression")),
+            String::from($sum),
+            Some(String::from($body)),
+        )
+    };
     ($hour:literal:$minute:literal $sum:literal) => {
         Reminder::new(When::Time($hour, $minute), String::from($sum), None)
     };
@@ -188,4 +773,95 @@ macro_rules! remind {
             Some(String::from($body)),
         )
     };
+    // anything followed by options, the tokens before the first option are collected first
+    (@split [$($reminder:tt)*] warn $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] warn $($opts)*)
+    };
+    (@split [$($reminder:tt)*] urgency $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] urgency $($opts)*)
+    };
+    (@split [$($reminder:tt)*] icon $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] icon $($opts)*)
+    };
+    (@split [$($reminder:tt)*] timeout $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] timeout $($opts)*)
+    };
+    (@split [$($reminder:tt)*] category $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] category $($opts)*)
+    };
+    (@split [$($reminder:tt)*] + $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] + $($opts)*)
+    };
+    (@split [$($reminder:tt)*] @ $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] @ $($opts)*)
+    };
+    (@split [$($reminder:tt)*] ! $($opts:tt)*) => {
+        $crate::remind!(@build [$($reminder)*] ! $($opts)*)
+    };
+    (@split [$($reminder:tt)*]) => {
+        compile_error!(concat!("remind!: unrecognised reminder `", stringify!($($reminder)*), "`"))
+    };
+    (@split [$($reminder:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::remind!(@split [$($reminder)* $next] $($rest)*)
+    };
+    (@build [$($reminder:tt)*] $($opts:tt)*) => {{
+        let mut reminder = $crate::remind!($($reminder)*);
+        $crate::remind!(@opts reminder $($opts)*);
+        reminder
+    }};
+    (@opts $r:ident) => {};
+    (@opts $r:ident + $tag:ident $($rest:tt)*) => {
+        let mut tags = $r.tags().to_vec();
+        tags.push(format!("+{}", stringify!($tag)));
+        $r.set_tags(tags);
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident @ $tag:ident $($rest:tt)*) => {
+        let mut tags = $r.tags().to_vec();
+        tags.push(format!("@{}", stringify!($tag)));
+        $r.set_tags(tags);
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident ! $priority:ident $($rest:tt)*) => {
+        $r.set_priority(stringify!($priority).parse().expect("invalid priority"));
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident warn $lead:literal, $($rest:tt)*) => {
+        $crate::remind!(@opts $r warn $lead);
+        $crate::remind!(@opts $r warn $($rest)*);
+    };
+    (@opts $r:ident warn $lead:literal $($rest:tt)*) => {
+        let mut warn = $r.warn().to_vec();
+        warn.push($crate::reminder::parse_lead(stringify!($lead)).expect("invalid lead time"));
+        $r.set_warn(warn);
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident urgency $urgency:ident $($rest:tt)*) => {
+        $r.set_urgency(stringify!($urgency).parse().expect("invalid urgency"));
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident icon $icon:literal $($rest:tt)*) => {
+        $r.set_icon(Some(String::from($icon)));
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident timeout never $($rest:tt)*) => {
+        $r.set_timeout(Some(0));
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident timeout $timeout:literal $($rest:tt)*) => {
+        $r.set_timeout(Some(
+            $crate::reminder::parse_lead(stringify!($timeout)).expect("invalid timeout"),
+        ));
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident category $category:literal $($rest:tt)*) => {
+        $r.set_category(Some(String::from($category)));
+        $crate::remind!(@opts $r $($rest)*);
+    };
+    (@opts $r:ident $($rest:tt)+) => {
+        compile_error!(concat!("remind!: unrecognised option `", stringify!($($rest)+), "`"))
+    };
+    ($($args:tt)+) => {
+        $crate::remind!(@split [] $($args)+)
+    };
 }
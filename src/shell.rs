@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use rustyline::{
+    completion::{Completer, Pair},
+    highlight::Highlighter,
+    hint::Hinter,
+    validate::Validator,
+    Context,
+};
+
+use crate::{
+    history,
+    lexer::{Lexer, TokenKind},
+    parser::DAYS,
+};
+
+// line editing for the interactive shell, completion and where history is kept
+
+const COMMANDS: [&str; 10] = [
+    "remind", "list", "edit", "rm", "done", "snooze", "undo", "redo", "help", "quit",
+];
+const WORDS: [&str; 8] = [
+    "cron", "tz", "warn", "urgency", "icon", "timeout", "category", "from",
+];
+const URGENCIES: [&str; 3] = ["low", "normal", "critical"];
+
+// $XDG_STATE_HOME/remember/history
+pub fn history_path() -> Option<PathBuf> {
+    Some(history::state_dir()?.join("history"))
+}
+
+// true when a string is left open, the shell then reads another line onto it
+pub fn incomplete(line: &str) -> bool {
+    Lexer::new(line.chars(), None).any(|t| t.kind == TokenKind::UnclosedStr)
+}
+
+// where the word being completed starts and what it could be, `ids` are the reminders in
+// the store
+pub fn complete(line: &str, pos: usize, ids: &[u32]) -> (usize, Vec<String>) {
+    let before = &line[..pos];
+    let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &before[start..];
+    if incomplete(before) {
+        return (pos, vec![]);
+    }
+    let words: Vec<&str> = before[..start].split_whitespace().collect();
+    let candidates: Vec<String> = match words.as_slice() {
+        [] => COMMANDS.iter().map(|c| c.to_string()).collect(),
+        ["edit" | "e" | "rm" | "delete" | "done" | "snooze"] => {
+            ids.iter().map(|id| id.to_string()).collect()
+        }
+        ["list" | "ls"] => vec![String::from("tz")],
+        [.., "urgency"] => URGENCIES.iter().map(|u| u.to_string()).collect(),
+        ["remind" | "r", ..] | ["edit" | "e", _, ..] => DAYS
+            .iter()
+            .chain(WORDS.iter())
+            .map(|w| w.to_string())
+            .collect(),
+        _ => vec![],
+    };
+    let candidates = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(word))
+        .collect();
+    (start, candidates)
+}
+
+#[derive(Default)]
+pub struct Helper {
+    pub ids: Vec<u32>,
+}
+
+impl Completer for Helper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let (start, candidates) = complete(line, pos, &self.ids);
+        let pairs = candidates
+            .into_iter()
+            .map(|c| Pair {
+                replacement: format!("{} ", c),
+                display: c,
+            })
+            .collect();
+        Ok((start, pairs))
+    }
+}
+
+impl Hinter for Helper {
+    type Hint = String;
+}
+
+impl Highlighter for Helper {}
+
+impl Validator for Helper {}
+
+impl rustyline::Helper for Helper {}
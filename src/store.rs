@@ -0,0 +1,775 @@
+use std::{
+    env,
+    fmt::Display,
+    fs::{self, File, OpenOptions, TryLockError},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+
+use crate::{parser, Reminder};
+
+// reminders added from the tui or over the control channel, kept apart from the reminder
+// files so those are never rewritten. every reminder gets an id that is never reused.
+// the store file is a snapshot, one reminder a line:
+//   <id> <created> <last|-> <snoozed|-> <warned|-> <count> <active|done> <remind statement>
+// (a line from before warned was kept has no <warned|-> and reads as never warned)
+// changes after it are appended to <store>.log instead of writing the snapshot again, so a
+// crash can only lose the change being written:
+//   <seq> <time> <created|edited|fired|dropped|warned|snoozed|completed|removed> <entry|id ->
+// every event has the whole entry as it became, opening the store plays the events newer than
+// the snapshot's seq over it, there is no snapshot until the log is first compacted. a last
+// line a crash cut short is dropped. once the log is long the snapshot is written again and
+// the log emptied
+//
+// every change made by a request is journaled so it can be undone and redone again, the
+// journal is kept beside the store in <store>.journal:
+//   undone <n>            how many of the newest ops were undone, those can be redone
+//   op <request>          the request that made the change
+//   before <entry|id ->   the entry as it was, "<id> -" when there was none
+//   after <entry|id ->    the entry it became, "<id> -" when it was removed
+//
+// only one process changes the store at a time, it holds an flock on <store>.lock while it
+// does. the daemon holds it for as long as it runs, a client without a daemon takes it for
+// each request and reads the store again first, see Store::lock. a save finds the newest seq
+// on disk first and is turned away if it is newer than the store's, nothing is written
+
+const JOURNAL_LIMIT: usize = 100;
+const COMPACT_AFTER: usize = 200;
+const LOCK_TRIES: usize = 50; //a tenth of a second apart
+
+#[derive(Debug, Clone)]
+struct Change {
+    id: u32,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct Op {
+    request: String,
+    changes: Vec<Change>,
+}
+
+// the entries as they were before a request, see Store::journal
+pub struct Snapshot(Vec<(u32, String)>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventKind {
+    Created,
+    Edited,
+    Fired,
+    Dropped,
+    Warned,
+    Snoozed,
+    Completed,
+    Removed,
+}
+
+impl EventKind {
+    // what turned one entry into the other
+    fn of(before: Option<&str>, after: Option<&str>) -> EventKind {
+        let (before, after) = match (before.and_then(fields), after.and_then(fields)) {
+            (None, _) => return EventKind::Created,
+            (_, None) => return EventKind::Removed,
+            (Some(before), Some(after)) => (before, after),
+        };
+        // id created last snoozed warned count state statement. only a notice that was shown
+        // counts, one dropped by quiet hours only moves last on
+        if before[1] != after[1] || before[7] != after[7] {
+            EventKind::Edited
+        } else if after[5] != before[5] {
+            EventKind::Fired
+        } else if after[2] != before[2] {
+            EventKind::Dropped
+        } else if after[6] == "done" && before[6] != "done" {
+            EventKind::Completed
+        } else if after[3] != "-" && after[3] != before[3] {
+            EventKind::Snoozed
+        } else if after[4] != before[4] {
+            EventKind::Warned
+        } else {
+            EventKind::Edited
+        }
+    }
+}
+
+impl Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self {
+            EventKind::Created => "created",
+            EventKind::Edited => "edited",
+            EventKind::Fired => "fired",
+            EventKind::Dropped => "dropped",
+            EventKind::Warned => "warned",
+            EventKind::Snoozed => "snoozed",
+            EventKind::Completed => "completed",
+            EventKind::Removed => "removed",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+impl FromStr for EventKind {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "created" => Ok(EventKind::Created),
+            "edited" => Ok(EventKind::Edited),
+            "fired" => Ok(EventKind::Fired),
+            "dropped" => Ok(EventKind::Dropped),
+            "warned" => Ok(EventKind::Warned),
+            "snoozed" => Ok(EventKind::Snoozed),
+            "completed" => Ok(EventKind::Completed),
+            "removed" => Ok(EventKind::Removed),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub seq: u64,
+    pub time: DateTime<Local>,
+    pub kind: EventKind,
+    pub id: u32,
+}
+
+// the store's lock, it is let go when this is dropped
+#[derive(Debug)]
+pub struct Lock {
+    _file: Option<File>,
+}
+
+#[derive(Debug)]
+pub struct Store {
+    path: Option<PathBuf>, //None keeps it in memory
+    next: u32,
+    entries: Vec<(u32, Reminder)>,
+    journal: Vec<Op>,
+    undone: usize,
+    seq: u64,                  //of the newest event
+    saved: Vec<(u32, String)>, //the entries as the snapshot and log have them
+    events: Vec<Event>,        //those in the log
+    compact: bool,             //the log lost its tail and has to be written again
+    rejournal: bool,           //the journal changed since it was written
+}
+
+impl Store {
+    pub fn new(path: Option<PathBuf>) -> Store {
+        Store {
+            path,
+            next: 1,
+            entries: vec![],
+            journal: vec![],
+            undone: 0,
+            seq: 0,
+            saved: vec![],
+            events: vec![],
+            compact: false,
+            rejournal: false,
+        }
+    }
+
+    // a store that doesnt exist yet is empty
+    pub fn open(path: PathBuf) -> Result<Store, StoreError> {
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(StoreError::Io(path, e)),
+        };
+        let mut store = Store::new(Some(path));
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(next) = line.strip_prefix("next ") {
+                store.next = next.parse().map_err(|_| store.corrupt(i, "bad next id"))?;
+                continue;
+            }
+            if let Some(seq) = line.strip_prefix("seq ") {
+                store.seq = seq.parse().map_err(|_| store.corrupt(i, "bad seq"))?;
+                continue;
+            }
+            let (id, reminder) = store.parse_entry(line).map_err(|e| store.corrupt(i, &e))?;
+            store.next = store.next.max(id + 1);
+            store.entries.push((id, reminder));
+        }
+        store.open_log()?;
+        store.saved = store.snapshot().0;
+        store.open_journal()?;
+        Ok(store)
+    }
+
+    // waits for the store's lock, then reads in what was saved since the store was opened.
+    // the store can be changed and saved until the lock is dropped
+    pub fn lock(&mut self) -> Result<Lock, StoreError> {
+        for _ in 0..LOCK_TRIES {
+            if let Some(lock) = self.try_lock()? {
+                return Ok(lock);
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Err(StoreError::Locked(self.path.clone().unwrap_or_default()))
+    }
+
+    // like lock, None if another process holds it
+    pub fn try_lock(&mut self) -> Result<Option<Lock>, StoreError> {
+        let Some(path) = self.path.as_ref().map(|path| path.with_extension("lock")) else {
+            return Ok(Some(Lock { _file: None }));
+        };
+        let io = |e| StoreError::Io(path.clone(), e);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(io)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(io(e)),
+        }
+        self.refresh()?;
+        Ok(Some(Lock { _file: Some(file) }))
+    }
+
+    // the entries and the journal as another process may have left them, so undo goes back
+    // over its changes too and the journal isnt written over with an old copy
+    fn refresh(&mut self) -> Result<(), StoreError> {
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+        let store = Store::open(path)?;
+        self.next = store.next;
+        self.entries = store.entries;
+        self.seq = store.seq;
+        self.saved = store.saved;
+        self.events = store.events;
+        self.compact = store.compact;
+        self.journal = store.journal;
+        self.undone = store.undone;
+        self.rejournal = false;
+        Ok(())
+    }
+
+    fn log_path(&self) -> Option<PathBuf> {
+        self.path.as_ref().map(|path| path.with_extension("log"))
+    }
+
+    fn open_log(&mut self) -> Result<(), StoreError> {
+        let Some(path) = self.log_path() else {
+            return Ok(());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(StoreError::Io(path, e)),
+        };
+        let snapshot = self.seq;
+        for (i, line) in text.split_inclusive('\n').enumerate() {
+            // every event is written with its newline, without one the rest never made it
+            if !line.ends_with('\n') {
+                self.compact = true;
+                break;
+            }
+            let (event, entry) = self
+                .parse_event(line.trim_end())
+                .map_err(|e| StoreError::Corrupt(path.clone(), i + 1, e))?;
+            if event.seq <= snapshot {
+                continue;
+            }
+            self.put(event.id, entry.as_deref());
+            self.seq = event.seq;
+            self.events.push(event);
+        }
+        Ok(())
+    }
+
+    fn parse_event(&self, line: &str) -> Result<(Event, Option<String>), String> {
+        let fields: Vec<&str> = line.splitn(4, ' ').collect();
+        let [seq, time, kind, entry] = fields[..] else {
+            return Err(String::from("missing fields"));
+        };
+        let (id, rest) = entry.split_once(' ').ok_or("missing fields")?;
+        let entry = match rest {
+            "-" => None,
+            _ => {
+                self.parse_entry(entry)?;
+                Some(entry.to_string())
+            }
+        };
+        let event = Event {
+            seq: seq.parse().map_err(|_| "bad seq")?,
+            time: DateTime::parse_from_rfc3339(time)
+                .map_err(|e| format!("bad time '{}': {}", time, e))?
+                .with_timezone(&Local),
+            kind: kind
+                .parse()
+                .map_err(|_| format!("unknown event '{}'", kind))?,
+            id: id.parse().map_err(|_| "bad id")?,
+        };
+        Ok((event, entry))
+    }
+
+    fn journal_path(&self) -> Option<PathBuf> {
+        self.path
+            .as_ref()
+            .map(|path| path.with_extension("journal"))
+    }
+
+    fn open_journal(&mut self) -> Result<(), StoreError> {
+        let Some(path) = self.journal_path() else {
+            return Ok(());
+        };
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(StoreError::Io(path, e)),
+        };
+        let corrupt =
+            |line: usize, what: &str| StoreError::Corrupt(path.clone(), line + 1, what.to_string());
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (kind, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match kind {
+                "undone" => self.undone = rest.parse().map_err(|_| corrupt(i, "bad undone"))?,
+                "op" => self.journal.push(Op {
+                    request: rest.to_string(),
+                    changes: vec![],
+                }),
+                "before" | "after" => {
+                    let (id, entry) = rest.split_once(' ').ok_or(corrupt(i, "missing fields"))?;
+                    let id: u32 = id.parse().map_err(|_| corrupt(i, "bad id"))?;
+                    let entry = match entry {
+                        "-" => None,
+                        _ => {
+                            self.parse_entry(rest).map_err(|e| corrupt(i, &e))?;
+                            Some(rest.to_string())
+                        }
+                    };
+                    let op = self
+                        .journal
+                        .last_mut()
+                        .ok_or(corrupt(i, "change before any op"))?;
+                    match (kind, op.changes.last_mut()) {
+                        ("after", Some(change)) if change.id == id => change.after = entry,
+                        ("before", _) => op.changes.push(Change {
+                            id,
+                            before: entry,
+                            after: None,
+                        }),
+                        _ => return Err(corrupt(i, "after without a before")),
+                    }
+                }
+                _ => return Err(corrupt(i, "unknown line")),
+            }
+        }
+        self.undone = self.undone.min(self.journal.len());
+        Ok(())
+    }
+
+    fn parse_entry(&self, line: &str) -> Result<(u32, Reminder), String> {
+        let Some([id, created, last, snoozed, warned, count, state, statement]) = fields(line)
+        else {
+            return Err(String::from("missing fields"));
+        };
+        let time = |text: &str| match text {
+            "-" => Ok(None),
+            text => DateTime::parse_from_rfc3339(text)
+                .map(|t| Some(t.with_timezone(&Local)))
+                .map_err(|e| format!("bad time '{}': {}", text, e)),
+        };
+        let mut reminder = parser::parse_reminder(statement).map_err(|e| e.to_string())?;
+        reminder.restore(
+            time(created)?.ok_or("no created time")?,
+            time(last)?,
+            time(snoozed)?,
+            time(warned)?,
+            count.parse().map_err(|_| "bad count")?,
+            state == "done",
+        );
+        Ok((id.parse().map_err(|_| "bad id")?, reminder))
+    }
+
+    fn corrupt(&self, line: usize, what: &str) -> StoreError {
+        let path = self.path.clone().unwrap_or_default();
+        StoreError::Corrupt(path, line + 1, what.to_string())
+    }
+
+    // appends what changed since the last save to the log, then writes the journal if it changed
+    pub fn save(&mut self) -> Result<(), StoreError> {
+        let (Some(path), Some(log), Some(journal)) =
+            (self.path.clone(), self.log_path(), self.journal_path())
+        else {
+            return Ok(());
+        };
+        // a process that didnt take the lock may have saved since, its events would be lost
+        if disk_seq(&path, &log)? > self.seq {
+            return Err(StoreError::Stale(path));
+        }
+        let now = Local::now();
+        let current = self.snapshot().0;
+        let mut text = String::new();
+        for change in diff(&self.saved, &current) {
+            self.seq += 1;
+            let event = Event {
+                seq: self.seq,
+                time: now,
+                kind: EventKind::of(change.before.as_deref(), change.after.as_deref()),
+                id: change.id,
+            };
+            let entry = change.after.unwrap_or(format!("{} -", change.id));
+            text.push_str(&format!(
+                "{} {} {} {}\n",
+                event.seq,
+                event.time.to_rfc3339(),
+                event.kind,
+                entry
+            ));
+            self.events.push(event);
+        }
+        self.saved = current;
+        if self.compact || self.events.len() > COMPACT_AFTER {
+            self.compact(&path, &log)?;
+        } else if !text.is_empty() {
+            append(&log, &text)?;
+        }
+
+        if !self.rejournal {
+            return Ok(());
+        }
+        let mut text = String::from("# written by remember, what undo and redo go through\n");
+        text.push_str(&format!("undone {}\n", self.undone));
+        for op in &self.journal {
+            text.push_str(&format!("op {}\n", op.request));
+            for change in &op.changes {
+                for (kind, entry) in [("before", &change.before), ("after", &change.after)] {
+                    match entry {
+                        Some(entry) => text.push_str(&format!("{} {}\n", kind, entry)),
+                        None => text.push_str(&format!("{} {} -\n", kind, change.id)),
+                    }
+                }
+            }
+        }
+        write(&journal, text)?;
+        self.rejournal = false;
+        Ok(())
+    }
+
+    // writes the snapshot again with everything in it and empties the log. a crash in between
+    // leaves events the snapshot already has, its seq says to skip them
+    fn compact(&mut self, path: &Path, log: &Path) -> Result<(), StoreError> {
+        let mut text = String::from("# written by remember, edit with remember tui\n");
+        text.push_str(&format!("next {}\nseq {}\n", self.next, self.seq));
+        for (id, r) in &self.entries {
+            text.push_str(&entry(*id, r));
+            text.push('\n');
+        }
+        write(path, text)?;
+        write(log, String::new())?;
+        self.events.clear();
+        self.compact = false;
+        Ok(())
+    }
+
+    // what happened to the store since the log was last compacted, oldest first
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(
+            self.entries
+                .iter()
+                .map(|(id, r)| (*id, entry(*id, r)))
+                .collect(),
+        )
+    }
+
+    // keeps what changed since the snapshot as one op, anything that was undone cant be redone
+    // after it. nothing is kept when nothing changed
+    pub fn journal(&mut self, request: &str, before: Snapshot) {
+        let changes = diff(&before.0, &self.snapshot().0);
+        if changes.is_empty() {
+            return;
+        }
+        self.journal.truncate(self.journal.len() - self.undone);
+        self.undone = 0;
+        self.journal.push(Op {
+            request: request.to_string(),
+            changes,
+        });
+        if self.journal.len() > JOURNAL_LIMIT {
+            self.journal.remove(0);
+        }
+        self.rejournal = true;
+    }
+
+    // puts back the entries the newest op changed, returns the request it undid
+    pub fn undo(&mut self) -> Option<String> {
+        let i = self.journal.len().checked_sub(self.undone + 1)?;
+        let op = self.journal[i].clone();
+        for change in &op.changes {
+            self.put(change.id, change.before.as_deref());
+        }
+        self.undone += 1;
+        self.rejournal = true;
+        Some(op.request)
+    }
+
+    // makes the last undone op again, returns its request
+    pub fn redo(&mut self) -> Option<String> {
+        if self.undone == 0 {
+            return None;
+        }
+        let op = self.journal[self.journal.len() - self.undone].clone();
+        for change in &op.changes {
+            self.put(change.id, change.after.as_deref());
+        }
+        self.undone -= 1;
+        self.rejournal = true;
+        Some(op.request)
+    }
+
+    // the requests that can be undone, newest first, then those that can be redone
+    pub fn history(&self) -> (Vec<&str>, Vec<&str>) {
+        let (done, undone) = self.journal.split_at(self.journal.len() - self.undone);
+        (
+            done.iter().rev().map(|op| op.request.as_str()).collect(),
+            undone.iter().map(|op| op.request.as_str()).collect(),
+        )
+    }
+
+    // the entry with this id as it was written, None takes it out. entries stay in id order
+    fn put(&mut self, id: u32, entry: Option<&str>) {
+        self.remove(id);
+        if let Some(Ok((_, r))) = entry.map(|e| self.parse_entry(e)) {
+            let i = self.entries.partition_point(|(i, _)| *i < id);
+            self.entries.insert(i, (id, r));
+            self.next = self.next.max(id + 1);
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn add(&mut self, reminder: Reminder) -> u32 {
+        let id = self.next;
+        self.next += 1;
+        self.entries.push((id, reminder));
+        id
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Reminder> {
+        self.entries.iter().find(|(i, _)| *i == id).map(|(_, r)| r)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Reminder> {
+        self.entries
+            .iter_mut()
+            .find(|(i, _)| *i == id)
+            .map(|(_, r)| r)
+    }
+
+    // swaps in an edited reminder under the same id, it counts from now like a new one
+    pub fn replace(&mut self, id: u32, reminder: Reminder) -> Option<Reminder> {
+        self.get_mut(id).map(|r| std::mem::replace(r, reminder))
+    }
+
+    pub fn remove(&mut self, id: u32) -> Option<Reminder> {
+        let i = self.entries.iter().position(|(i, _)| *i == id)?;
+        Some(self.entries.remove(i).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &Reminder)> {
+        self.entries.iter().map(|(id, r)| (*id, r))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut Reminder)> {
+        self.entries.iter_mut().map(|(id, r)| (*id, r))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// the fields of an entry, one without a warned time gets "-" for it
+fn fields(entry: &str) -> Option<[&str; 8]> {
+    let fields: Vec<&str> = entry.splitn(8, ' ').collect();
+    if fields
+        .get(4)
+        .is_some_and(|count| count.parse::<u32>().is_ok())
+    {
+        let mut fields: Vec<&str> = entry.splitn(7, ' ').collect();
+        if fields.len() == 7 {
+            fields.insert(4, "-");
+        }
+        return fields.try_into().ok();
+    }
+    fields.try_into().ok()
+}
+
+// a reminder as a line of the store
+fn entry(id: u32, r: &Reminder) -> String {
+    let time = |t: Option<DateTime<Local>>| match t {
+        Some(t) => t.to_rfc3339(),
+        None => String::from("-"),
+    };
+    format!(
+        "{} {} {} {} {} {} {} {}",
+        id,
+        r.created().to_rfc3339(),
+        time(r.last()),
+        time(r.snoozed()),
+        time(r.warned()),
+        r.count(),
+        if r.is_done() { "done" } else { "active" },
+        r
+    )
+}
+
+// written to a temporary file first so a crash cant leave half a file behind
+fn write(path: &Path, text: String) -> Result<(), StoreError> {
+    let tmp = path.with_extension("tmp");
+    let io = |e| StoreError::Io(path.to_path_buf(), e);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io)?;
+    }
+    let mut file = File::create(&tmp).map_err(io)?;
+    file.write_all(text.as_bytes()).map_err(io)?;
+    file.sync_all().map_err(io)?;
+    fs::rename(&tmp, path).map_err(io)
+}
+
+// the seq of the newest event in the snapshot or the log, a line a crash cut short isnt one
+fn disk_seq(path: &Path, log: &Path) -> Result<u64, StoreError> {
+    let read = |path: &Path| match fs::read_to_string(path) {
+        Ok(text) => Ok(text),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(StoreError::Io(path.to_path_buf(), e)),
+    };
+    let snapshot = read(path)?
+        .lines()
+        .find_map(|line| line.strip_prefix("seq ")?.parse().ok())
+        .unwrap_or(0);
+    let log = read(log)?
+        .split_inclusive('\n')
+        .filter(|line| line.ends_with('\n'))
+        .filter_map(|line| line.split(' ').next()?.parse().ok())
+        .max()
+        .unwrap_or(0);
+    Ok(snapshot.max(log))
+}
+
+// the events are on disk before the change is reported done
+fn append(path: &Path, text: &str) -> Result<(), StoreError> {
+    let io = |e| StoreError::Io(path.to_path_buf(), e);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(io)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(io)?;
+    file.write_all(text.as_bytes()).map_err(io)?;
+    file.sync_data().map_err(io)
+}
+
+// the entries that differ between two snapshots, by id
+fn diff(before: &[(u32, String)], after: &[(u32, String)]) -> Vec<Change> {
+    let find = |entries: &[(u32, String)], id: u32| {
+        entries
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, e)| e.clone())
+    };
+    let mut changes: Vec<Change> = before
+        .iter()
+        .filter(|(id, e)| find(after, *id).as_ref() != Some(e))
+        .map(|(id, e)| Change {
+            id: *id,
+            before: Some(e.clone()),
+            after: find(after, *id),
+        })
+        .collect();
+    changes.extend(
+        after
+            .iter()
+            .filter(|(id, _)| find(before, *id).is_none())
+            .map(|(id, e)| Change {
+                id: *id,
+                before: None,
+                after: Some(e.clone()),
+            }),
+    );
+    changes
+}
+
+fn data_dir() -> Option<PathBuf> {
+    let data_home = match env::var_os("XDG_DATA_HOME") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(env::var_os("HOME")?).join(".local/share"),
+    };
+    Some(data_home.join("remember"))
+}
+
+// $XDG_DATA_HOME/remember/store
+pub fn default_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("store"))
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(PathBuf, io::Error),
+    Corrupt(PathBuf, usize, String), //line
+    Locked(PathBuf),                 //another process kept the lock
+    Stale(PathBuf),                  //saved by another process since it was read
+}
+
+impl Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            StoreError::Corrupt(path, line, what) => {
+                write!(f, "{}:{}: corrupt store, {}", path.display(), line, what)
+            }
+            StoreError::Locked(path) => {
+                write!(f, "{}: in use by another remember", path.display())
+            }
+            StoreError::Stale(path) => write!(
+                f,
+                "{}: changed by another remember since it was read",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Io(_, e) => Some(e),
+            StoreError::Corrupt(..) | StoreError::Locked(_) | StoreError::Stale(_) => None,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Local};
+
+// expanded when a reminder is shown rather than when it is parsed
+pub const BUILTINS: [&str; 3] = ["date", "weekday", "count"];
+
+// replaces every ${name} in text with lookup(name), a name lookup doesnt know is
+// returned as the error along with its byte offset in text
+pub fn expand<F: Fn(&str) -> Option<String>>(
+    text: &str,
+    lookup: F,
+) -> Result<String, (usize, String)> {
+    let mut expanded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let name = &rest[start + 2..end];
+        match lookup(name) {
+            Some(value) => {
+                expanded.push_str(&rest[..start]);
+                expanded.push_str(&value);
+            }
+            None => return Err((text.len() - rest.len() + start, name.to_string())),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+pub fn builtin(name: &str, now: DateTime<Local>, count: u32) -> Option<String> {
+    match name {
+        "date" => Some(now.format("%d/%m/%Y").to_string()),
+        "weekday" => Some(now.format("%A").to_string()),
+        "count" => Some(count.to_string()),
+        _ => None,
+    }
+}
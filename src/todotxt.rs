@@ -0,0 +1,111 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, Timelike};
+
+use crate::{
+    lexer::Loc,
+    parser::{Parsed, ParserError},
+    Reminder, When,
+};
+
+// todo.txt, a task needs a due:YYYY-MM-DD tag to become a reminder. at:HH:MM sets
+// the time, without one the reminder goes off at 9:00. +projects and @contexts are tags
+
+pub fn import(text: &str, file_path: Option<String>) -> Parsed {
+    let mut parsed = Parsed::default();
+    for (lnum, line) in text.lines().enumerate() {
+        let loc = |col: usize| Loc {
+            file_path: file_path.clone(),
+            row: lnum + 1,
+            col,
+        };
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.starts_with("x ") {
+            continue;
+        }
+
+        let mut summary = vec![];
+        let mut tags = vec![];
+        let mut due = None;
+        let mut at = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+        let mut error = None;
+        for (i, (col, word)) in words(line).enumerate() {
+            let is_date = NaiveDate::parse_from_str(word, "%Y-%m-%d").is_ok();
+            if (i == 0 && is_priority(word)) || (i <= 1 && is_date && summary.is_empty()) {
+                continue; //priority and creation date
+            }
+            if let Some(date) = word.strip_prefix("due:") {
+                match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                    Ok(date) => due = Some(date),
+                    Err(_) => error = Some(unsupported(loc(col), "due date", date)),
+                }
+            } else if let Some(time) = word.strip_prefix("at:") {
+                match NaiveTime::parse_from_str(time, "%H:%M") {
+                    Ok(time) => at = time,
+                    Err(_) => error = Some(unsupported(loc(col), "time", time)),
+                }
+            } else if (word.starts_with('+') || word.starts_with('@')) && word.len() > 1 {
+                tags.push(word.to_string());
+            } else {
+                summary.push(word);
+            }
+        }
+
+        if let Some(e) = error {
+            parsed.errors.push(e);
+            continue;
+        }
+        let due = match due {
+            Some(due) => due,
+            None => {
+                parsed.errors.push(ParserError::Unsupported(
+                    loc(1),
+                    String::from("task without a due: date"),
+                ));
+                continue;
+            }
+        };
+
+        let when = When::Date(due.day(), due.month(), due.year(), at.hour(), at.minute());
+        let mut reminder = Reminder::new(when, summary.join(" "), None);
+        reminder.set_tags(tags);
+        parsed.reminders.push(reminder);
+    }
+    parsed
+}
+
+// one task per reminder, due on its next occurrence. recurrence and bodies dont fit in todo.txt
+pub fn export(reminders: &[Reminder], now: DateTime<Local>) -> String {
+    let mut out = String::new();
+    for r in reminders {
+        let due = match r.due(now) {
+            Some(due) => due,
+            None => continue,
+        };
+        out.push_str(r.summary());
+        for tag in r.tags() {
+            out.push(' ');
+            out.push_str(tag);
+        }
+        out.push_str(&due.format(" due:%Y-%m-%d at:%H:%M\n").to_string());
+    }
+    out
+}
+
+fn is_priority(word: &str) -> bool {
+    let word = word.as_bytes();
+    word.len() == 3 && word[0] == b'(' && word[1].is_ascii_uppercase() && word[2] == b')'
+}
+
+// every word along with the column it starts at
+fn words(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(' ')
+        .scan(1, |col, word| {
+            let start = *col;
+            *col += word.chars().count() + 1;
+            Some((start, word))
+        })
+        .filter(|(_, word)| !word.is_empty())
+}
+
+fn unsupported(loc: Loc, what: &str, text: &str) -> ParserError {
+    ParserError::Unsupported(loc, format!("{} '{}'", what, text))
+}
@@ -0,0 +1,401 @@
+use std::{io, time::Duration};
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+
+use crate::{
+    agenda,
+    control::Control,
+    parser::{self, ParserError},
+    reminder, Reminder,
+};
+
+// remember tui, the reminders in the store sorted by when they are next due.
+// everything goes through the control channel, so it works the same with or without a daemon
+
+const HELP: &str = "a add  e edit  s snooze  d delete  x done  u undo  U redo  r refresh  q quit";
+
+struct Row {
+    id: u32,
+    due: Option<DateTime<Local>>,
+    state: String,
+    reminder: Reminder,
+}
+
+enum Mode {
+    Browse,
+    Add,
+    Edit(u32),
+    Snooze(u32),
+    Delete(u32),
+}
+
+struct App {
+    control: Control,
+    rows: Vec<Row>,
+    list: ListState,
+    mode: Mode,
+    input: String,
+    cursor: usize, //in chars
+    status: Result<String, String>,
+}
+
+pub fn run(control: Control) -> io::Result<()> {
+    let status = match &control {
+        Control::Daemon(path) => format!("connected to the daemon at {}", path.display()),
+        Control::Store(..) => String::from("no daemon running, editing the store directly"),
+    };
+    let mut app = App {
+        control,
+        rows: vec![],
+        list: ListState::default(),
+        mode: Mode::Browse,
+        input: String::new(),
+        cursor: 0,
+        status: Ok(status),
+    };
+    app.refresh();
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            // redraws now and then so due times and changes from other clients show up
+            if !event::poll(Duration::from_secs(2))? {
+                if matches!(self.mode, Mode::Browse) {
+                    self.refresh();
+                }
+                continue;
+            }
+            match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press && !self.key(key) => {
+                    return Ok(())
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn refresh(&mut self) {
+        let reply = match self.control.request("list") {
+            Ok(reply) => reply,
+            Err(e) => {
+                self.status = Err(e);
+                return;
+            }
+        };
+        let selected = self.selected().map(|row| row.id);
+        self.rows = reply.lines().filter_map(parse_row).collect();
+        let index = selected
+            .and_then(|id| self.rows.iter().position(|row| row.id == id))
+            .or(if self.rows.is_empty() { None } else { Some(0) });
+        self.list
+            .select(index.map(|i| i.min(self.rows.len().saturating_sub(1))));
+    }
+
+    fn selected(&self) -> Option<&Row> {
+        self.list.selected().and_then(|i| self.rows.get(i))
+    }
+
+    // false once the tui should close
+    fn key(&mut self, key: KeyEvent) -> bool {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        match self.mode {
+            Mode::Browse => return self.browse_key(key),
+            Mode::Delete(id) => {
+                if key.code == KeyCode::Char('y') {
+                    self.send(format!("rm {}", id), "deleted");
+                } else {
+                    self.status = Ok(String::from("not deleted"));
+                }
+                self.mode = Mode::Browse;
+            }
+            _ => self.input_key(key),
+        }
+        true
+    }
+
+    fn browse_key(&mut self, key: KeyEvent) -> bool {
+        let selected = self
+            .selected()
+            .map(|row| (row.id, row.reminder.to_string()));
+        match (key.code, selected) {
+            (KeyCode::Char('q') | KeyCode::Esc, _) => return false,
+            (KeyCode::Down | KeyCode::Char('j'), _) => self.list.select_next(),
+            (KeyCode::Up | KeyCode::Char('k'), _) => self.list.select_previous(),
+            (KeyCode::Home | KeyCode::Char('g'), _) => self.list.select_first(),
+            (KeyCode::End | KeyCode::Char('G'), _) => self.list.select_last(),
+            (KeyCode::Char('r'), _) => self.refresh(),
+            (KeyCode::Char('a'), _) => self.prompt(Mode::Add, String::from("remind ")),
+            (KeyCode::Char('e') | KeyCode::Enter, Some((id, statement))) => {
+                self.prompt(Mode::Edit(id), statement)
+            }
+            (KeyCode::Char('s'), Some((id, _))) => {
+                self.prompt(Mode::Snooze(id), String::from("10m"))
+            }
+            (KeyCode::Char('d') | KeyCode::Delete, Some((id, _))) => {
+                self.mode = Mode::Delete(id);
+                self.status = Ok(format!("delete reminder {}? y/n", id));
+            }
+            (KeyCode::Char('x'), Some((id, _))) => self.send(format!("done {}", id), "marked done"),
+            (KeyCode::Char('u'), _) => self.undo("undo", "undid"),
+            (KeyCode::Char('U'), _) => self.undo("redo", "redid"),
+            _ => {}
+        }
+        true
+    }
+
+    fn prompt(&mut self, mode: Mode, input: String) {
+        self.mode = mode;
+        self.cursor = input.chars().count();
+        self.input = input;
+    }
+
+    fn input_key(&mut self, key: KeyEvent) {
+        let byte = |input: &str, cursor: usize| {
+            input
+                .char_indices()
+                .nth(cursor)
+                .map_or(input.len(), |(i, _)| i)
+        };
+        match key.code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Enter => self.submit(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.input.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.input.chars().count(),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.input.remove(byte(&self.input, self.cursor));
+            }
+            KeyCode::Delete if self.cursor < self.input.chars().count() => {
+                self.input.remove(byte(&self.input, self.cursor));
+            }
+            KeyCode::Char(c) => {
+                self.input.insert(byte(&self.input, self.cursor), c);
+                self.cursor += 1;
+            }
+            _ => {}
+        }
+    }
+
+    // the reminder or lead time being typed, checked on every key so mistakes show inline
+    fn diagnostic(&self) -> Option<(usize, String)> {
+        match self.mode {
+            Mode::Add | Mode::Edit(_) => match parser::parse_reminder(&self.input) {
+                Ok(_) => None,
+                Err(e) => Some((e.loc().col, message(&e))),
+            },
+            Mode::Snooze(_) => match reminder::parse_lead(self.input.trim()) {
+                Some(_) => None,
+                None => Some((1, String::from("expected a number of s/m/h/d/w eg: 10m"))),
+            },
+            _ => None,
+        }
+    }
+
+    fn submit(&mut self) {
+        if let Some((_, e)) = self.diagnostic() {
+            self.status = Err(e);
+            return;
+        }
+        let input = self.input.trim().to_string();
+        match self.mode {
+            Mode::Add => self.send(format!("add {}", input), "added"),
+            Mode::Edit(id) => self.send(format!("edit {} {}", id, input), "saved"),
+            Mode::Snooze(id) => self.send(format!("snooze {} {}", id, input), "snoozed"),
+            _ => {}
+        }
+        if self.status.is_ok() {
+            self.mode = Mode::Browse;
+        }
+    }
+
+    fn send(&mut self, request: String, done: &str) {
+        self.status = self.control.request(&request).map(|_| done.to_string());
+        self.refresh();
+    }
+
+    // the reply is the request undone or redone, it may have come from another client
+    fn undo(&mut self, request: &str, done: &str) {
+        self.status = self
+            .control
+            .request(request)
+            .map(|reply| format!("{} {}", done, reply));
+        self.refresh();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, input, status] = Layout::vertical([
+            Constraint::Min(3),
+            Constraint::Length(if matches!(self.mode, Mode::Browse | Mode::Delete(_)) {
+                0
+            } else {
+                4
+            }),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list, detail] =
+            Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                .areas(main);
+
+        let now = Local::now();
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let style = match row.state.as_str() {
+                    "done" => Style::default().fg(Color::DarkGray),
+                    "snoozed" => Style::default().fg(Color::Yellow),
+                    _ => Style::default(),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:>4} ", row.id),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(
+                        format!(
+                            "{:<10} ",
+                            row.due
+                                .map_or(String::from("-"), |due| agenda::relative(due, now))
+                        ),
+                        style,
+                    ),
+                    Span::styled(row.reminder.summary().to_string(), style),
+                ]))
+            })
+            .collect();
+        let title = format!(" reminders ({}) ", self.rows.len());
+        frame.render_stateful_widget(
+            List::new(items)
+                .block(Block::bordered().title(title))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            list,
+            &mut self.list,
+        );
+
+        let text = match self.selected() {
+            Some(row) => details(row),
+            None => vec![Line::from("no reminders, press a to add one")],
+        };
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::bordered().title(" details "))
+                .wrap(Wrap { trim: false }),
+            detail,
+        );
+
+        if !matches!(self.mode, Mode::Browse | Mode::Delete(_)) {
+            self.draw_input(frame, input);
+        }
+
+        let line = match &self.status {
+            Ok(text) if text.is_empty() => {
+                Line::from(HELP).style(Style::default().fg(Color::DarkGray))
+            }
+            Ok(text) => Line::from(format!("{}    {}", text, HELP)),
+            Err(e) => Line::from(format!("error: {}", e)).style(Style::default().fg(Color::Red)),
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+
+    fn draw_input(&self, frame: &mut Frame, area: ratatui::layout::Rect) {
+        let title = match self.mode {
+            Mode::Add => String::from(" add "),
+            Mode::Edit(id) => format!(" edit {} ", id),
+            Mode::Snooze(id) => format!(" snooze {} for ", id),
+            _ => String::new(),
+        };
+        let mut lines = vec![Line::from(self.input.as_str())];
+        if let Some((col, e)) = self.diagnostic() {
+            let caret = format!("{}^ {}", " ".repeat(col.saturating_sub(1)), e);
+            lines.push(Line::from(caret).style(Style::default().fg(Color::Red)));
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+        frame.set_cursor_position((area.x + 1 + self.cursor as u16, area.y + 1));
+    }
+}
+
+// a line of the list reply: id, due, state and statement split by tabs
+fn parse_row(line: &str) -> Option<Row> {
+    let mut fields = line.splitn(4, '\t');
+    let id = fields.next()?.parse().ok()?;
+    let due = DateTime::parse_from_rfc3339(fields.next()?)
+        .ok()
+        .map(|t| t.with_timezone(&Local));
+    let state = fields.next()?.to_string();
+    let reminder = parser::parse_reminder(fields.next()?).ok()?;
+    Some(Row {
+        id,
+        due,
+        state,
+        reminder,
+    })
+}
+
+// the message without the location, the caret already points at it
+fn message(e: &ParserError) -> String {
+    let text = e.to_string();
+    let loc = e.loc().to_string();
+    text.strip_prefix(&loc)
+        .map(|rest| rest.trim_start_matches(':').trim().to_string())
+        .unwrap_or(text)
+}
+
+fn details(row: &Row) -> Vec<Line<'static>> {
+    let r = &row.reminder;
+    let field = |name: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{:<9}", name), Style::default().fg(Color::DarkGray)),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        field("summary", r.summary().to_string()),
+        field("when", r.when().to_string()),
+        field(
+            "next",
+            row.due
+                .map(|t| t.format("%a %d/%m/%Y %H:%M").to_string())
+                .unwrap_or_else(|| String::from("never")),
+        ),
+        field("state", row.state.clone()),
+        field("urgency", r.urgency().to_string()),
+    ];
+    if !r.warn().is_empty() {
+        let warn: Vec<String> = r
+            .warn()
+            .iter()
+            .map(|lead| reminder::lead_text(*lead))
+            .collect();
+        lines.push(field("warn", warn.join(", ")));
+    }
+    if let Some(from) = r.from() {
+        lines.push(field("from", from.format("%d/%m/%Y").to_string()));
+    }
+    if let Some(body) = r.body() {
+        lines.push(Line::from(""));
+        lines.extend(body.lines().map(|line| Line::from(line.to_string())));
+    }
+    lines
+}
@@ -0,0 +1,71 @@
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+
+use inotify::{Inotify, WatchDescriptor, WatchMask};
+
+use crate::parser;
+
+// watches reminder files for changes, editors often replace the file instead of
+// writing to it, so the directory a file is in gets watched rather than the file
+pub struct Watcher {
+    inotify: Inotify,
+    watches: HashMap<WatchDescriptor, Option<Vec<OsString>>>, //None means any file in the directory
+    buffer: [u8; 4096],
+}
+
+impl Watcher {
+    pub fn new(files: &[PathBuf], dirs: &[PathBuf]) -> io::Result<Watcher> {
+        let mask = WatchMask::CLOSE_WRITE
+            | WatchMask::MOVED_TO
+            | WatchMask::MOVED_FROM
+            | WatchMask::CREATE
+            | WatchMask::DELETE;
+
+        let inotify = Inotify::init()?;
+        let mut watches: HashMap<WatchDescriptor, Option<Vec<OsString>>> = HashMap::new();
+        for dir in dirs {
+            let wd = inotify.watches().add(dir, mask)?;
+            watches.insert(wd, None);
+        }
+        for file in files {
+            let (dir, name) = match (file.parent(), file.file_name()) {
+                (Some(dir), Some(name)) => (dir, name.to_os_string()),
+                _ => continue,
+            };
+            let wd = inotify.watches().add(dir, mask)?;
+            // a directory that is watched whole already covers the file
+            if let Some(names) = watches.entry(wd).or_insert_with(|| Some(vec![])) {
+                names.push(name);
+            }
+        }
+
+        Ok(Watcher {
+            inotify,
+            watches,
+            buffer: [0; 4096],
+        })
+    }
+
+    // drains pending events without blocking, true if a watched file changed
+    pub fn changed(&mut self) -> io::Result<bool> {
+        let mut changed = false;
+        loop {
+            let events = match self.inotify.read_events(&mut self.buffer) {
+                Ok(events) => events,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(changed),
+                Err(e) => return Err(e),
+            };
+            for event in events {
+                changed |= match (self.watches.get(&event.wd), event.name) {
+                    (Some(None), Some(name)) => !parser::is_skipped(Path::new(name)),
+                    (Some(Some(names)), Some(name)) => names.iter().any(|n| n == name),
+                    _ => false,
+                };
+            }
+        }
+    }
+}
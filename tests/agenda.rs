@@ -0,0 +1,80 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use remember::{agenda, remind, Reminder, When};
+
+fn local(d: u32, h: u32, m: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2022, 5, d, h, m, 0).unwrap()
+}
+
+#[test]
+fn occurrences() {
+    // 2022-05-13 is a friday
+    let now = local(13, 9, 30);
+    let daily = remind!(9:00 "standup");
+    assert_eq!(
+        agenda::occurrences(&daily, now, now + Duration::days(3)),
+        [local(14, 9, 0), local(15, 9, 0), local(16, 9, 0)]
+    );
+    let weekly = remind!(mon 10:30 "plan");
+    assert_eq!(
+        agenda::occurrences(&weekly, now, now + Duration::days(14)),
+        [local(16, 10, 30), local(23, 10, 30)]
+    );
+    let cron = remind!(cron "0 */8 * * *" "stretch");
+    assert_eq!(
+        agenda::occurrences(&cron, now, now + Duration::days(1)).len(),
+        3
+    );
+
+    let mut snoozed = Reminder::new(When::Date(1, 1, 2100, 9, 0), String::from("x"), None);
+    snoozed.snooze(local(13, 12, 0));
+    assert_eq!(
+        agenda::occurrences(&snoozed, now, now + Duration::days(1)),
+        [local(13, 12, 0)]
+    );
+    snoozed.set_done();
+    assert!(agenda::occurrences(&snoozed, now, now + Duration::days(1)).is_empty());
+}
+
+#[test]
+fn grouped_by_day() {
+    let now = local(13, 9, 30);
+    let daily = remind!(9:00 "standup");
+    let once = remind!(14/5/2022 8:00 "dentist");
+    let days = agenda::agenda(&[&daily, &once], now, 2);
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[0].0, NaiveDate::from_ymd_opt(2022, 5, 14).unwrap());
+    let summaries: Vec<&str> = days[0].1.iter().map(|(_, r)| r.summary()).collect();
+    assert_eq!(summaries, ["dentist", "standup"]);
+    assert_eq!(days[1].1.len(), 1);
+}
+
+#[test]
+fn relative() {
+    let now = local(13, 9, 0);
+    assert_eq!(agenda::relative(local(13, 9, 0), now), "now");
+    assert_eq!(agenda::relative(local(13, 9, 45), now), "in 45m");
+    assert_eq!(agenda::relative(local(13, 11, 30), now), "in 2h");
+    assert_eq!(agenda::relative(local(16, 9, 0), now), "in 3d");
+    assert_eq!(agenda::relative(local(13, 7, 0), now), "2h ago");
+}
+
+#[test]
+fn month() {
+    let grid = agenda::month(2022, 5, &[1, 13, 31]).unwrap();
+    let expected = [
+        "          May 2022",
+        " Mo  Tu  We  Th  Fr  Sa  Su",
+        "                          1*",
+        "  2   3   4   5   6   7   8",
+        "  9  10  11  12  13* 14  15",
+        " 16  17  18  19  20  21  22",
+        " 23  24  25  26  27  28  29",
+        " 30  31*",
+    ];
+    assert_eq!(grid.lines().collect::<Vec<_>>(), expected);
+    assert_eq!(agenda::month(2022, 13, &[]), None);
+
+    let now = local(13, 9, 30);
+    let weekly = remind!(mon 10:30 "plan");
+    assert_eq!(agenda::marked(&[&weekly], 2022, 5, now), [16, 23, 30]);
+}
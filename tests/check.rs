@@ -0,0 +1,63 @@
+use chrono::{Datelike, Duration, Local, Timelike};
+use remember::{Reminder, When};
+
+fn reminder(when: When) -> Reminder {
+    Reminder::new(when, String::from("summary"), None)
+}
+
+#[test]
+fn duration() {
+    let mut r = reminder(When::Duration(60));
+    let now = Local::now();
+    assert!(!r.check(now));
+    assert!(r.check(now + Duration::seconds(61)));
+    assert!(!r.is_done());
+}
+
+#[test]
+fn time_goes_off_at_its_next_occurrence() {
+    let later = Local::now() + Duration::hours(2);
+    let mut r = reminder(When::Time(later.hour(), later.minute()));
+    let time = r.next_fire_time(Local::now()).unwrap();
+    assert!(!r.check(time - Duration::hours(1)));
+    assert!(r.check(time));
+    // still due once the minute has rolled past the one it was set for
+    assert!(r.check(time + Duration::minutes(59)));
+    assert!(!r.is_done());
+}
+
+#[test]
+fn time_past_the_hour() {
+    let later = Local::now() + Duration::hours(3);
+    let mut r = reminder(When::Time(later.hour(), 30));
+    let time = r.next_fire_time(Local::now()).unwrap();
+    assert!(r.check(time + Duration::minutes(30)));
+}
+
+#[test]
+fn day() {
+    let later = Local::now() + Duration::days(2);
+    let mut r = reminder(When::Day(later.weekday().to_string(), 9, 0));
+    let time = r.next_fire_time(Local::now()).unwrap();
+    assert_eq!(time.weekday(), later.weekday());
+    assert!(!r.check(time - Duration::days(1)));
+    assert!(r.check(time));
+    assert!(r.check(time + Duration::days(1)));
+}
+
+#[test]
+fn past_date_never_goes_off() {
+    let mut r = reminder(When::Date(1, 1, 2000, 9, 0));
+    assert!(!r.check(Local::now()));
+    assert!(r.is_done());
+}
+
+#[test]
+fn date_any_month() {
+    let mut r = reminder(When::Date(1, 0, 0, 9, 0));
+    let time = r.next_fire_time(Local::now()).unwrap();
+    assert_eq!((time.day(), time.hour()), (1, 9));
+    assert!(!r.check(time - Duration::minutes(1)));
+    assert!(r.check(time));
+    assert!(!r.is_done());
+}
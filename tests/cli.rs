@@ -0,0 +1,224 @@
+mod common;
+
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+use common::temp;
+
+// the store, history and socket are kept in `dir`, away from a deamon that might be running
+fn remember(dir: &PathBuf, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_remember"))
+        .args(args)
+        .env("XDG_DATA_HOME", dir)
+        .env("XDG_RUNTIME_DIR", dir)
+        .env("XDG_CONFIG_HOME", dir)
+        .env("XDG_STATE_HOME", dir)
+        .output()
+        .unwrap()
+}
+
+// the interactive shell, or another mode that reads stdin, with `input` as its lines
+fn shell(dir: &PathBuf, args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_remember"))
+        .args(args)
+        .env("XDG_DATA_HOME", dir)
+        .env("XDG_RUNTIME_DIR", dir)
+        .env("XDG_CONFIG_HOME", dir)
+        .env("XDG_STATE_HOME", dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn usage() {
+    let dir = temp("usage");
+    let help = remember(&dir, &["--help"]);
+    assert!(help.status.success());
+    assert!(stdout(&help).contains("agenda"));
+
+    assert_eq!(remember(&dir, &["frobnicate"]).status.code(), Some(2));
+    assert_eq!(
+        remember(&dir, &["agenda", "--days", "x"]).status.code(),
+        Some(2)
+    );
+    assert_eq!(remember(&dir, &["add"]).status.code(), Some(2));
+    assert_eq!(remember(&dir, &["rm", "one"]).status.code(), Some(2));
+}
+
+#[test]
+fn store_commands() {
+    let dir = temp("store_commands");
+    let add = remember(
+        &dir,
+        &["add", "mon", "9:00", "plan the week", "warn", "15m"],
+    );
+    assert!(add.status.success());
+    assert_eq!(stdout(&add), "1\n");
+    let add = remember(&dir, &["add", "10:00 \"tea\" \"https://example.com\""]);
+    assert_eq!(stdout(&add), "2\n");
+
+    let bad = remember(&dir, &["add", "9:61", "x"]);
+    assert_eq!(bad.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("ERROR ::"));
+
+    assert!(remember(&dir, &["edit", "1", "tue", "9:00", "plan"])
+        .status
+        .success());
+    assert!(remember(&dir, &["done", "2"]).status.success());
+    let list = stdout(&remember(&dir, &["list", "--format", "tsv"]));
+    let lines: Vec<Vec<&str>> = list.lines().map(|l| l.split('\t').collect()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(
+        lines[0][2..],
+        ["active", "plan", "", "tue 9:00", "", "normal"]
+    );
+    assert_eq!(
+        lines[1][2..],
+        ["done", "tea", "https://example.com", "10:00", "", "normal"]
+    );
+
+    assert!(remember(&dir, &["rm", "1"]).status.success());
+    let list = stdout(&remember(&dir, &["list", "--format", "tsv"]));
+    assert_eq!(list.lines().count(), 1);
+}
+
+#[test]
+fn add_words() {
+    let dir = temp("add_words");
+    let add = |args: &[&str]| remember(&dir, &[&["add"], args].concat());
+    assert!(add(&["30", "it's late"]).status.success());
+    assert!(add(&["30", "say \"hi\" now"]).status.success());
+    // plain words and keywords are text once the trigger is read
+    assert!(add(&["30", "warn", "e"]).status.success());
+    assert!(add(&[
+        "9:00",
+        "tz",
+        "Europe/London",
+        "sync",
+        "+work",
+        "warn",
+        "5m,1h"
+    ])
+    .status
+    .success());
+    // both kinds of quote and a trailing backslash are escaped
+    assert!(add(&["30", "it's \"x\"", "C:\\dir\\"]).status.success());
+
+    let minutes = add(&["10m", "tea"]);
+    assert_eq!(minutes.status.code(), Some(1));
+    assert!(
+        String::from_utf8_lossy(&minutes.stderr).contains("remember add 600 \"tea\""),
+        "{}",
+        String::from_utf8_lossy(&minutes.stderr)
+    );
+
+    let list = stdout(&remember(&dir, &["list", "--format", "tsv"]));
+    let mut rows: Vec<Vec<&str>> = list.lines().map(|l| l.split('\t').collect()).collect();
+    rows.sort_by_key(|row| row[0].parse::<u32>().unwrap());
+    let texts: Vec<(&str, &str)> = rows.iter().map(|row| (row[3], row[4])).collect();
+    assert_eq!(
+        texts,
+        [
+            ("it's late", ""),
+            ("say \"hi\" now", ""),
+            ("warn", "e"),
+            ("sync", ""),
+            ("it's \"x\"", "C:\\dir\\")
+        ]
+    );
+    assert_eq!(rows[3][5], "9:00 tz \"Europe/London\"");
+}
+
+#[test]
+fn shell_list() {
+    let dir = temp("shell_list");
+    let output = shell(
+        &dir,
+        &[],
+        "remind cron \"0 9 * * 1-5\" \"standup\" warn 5m\nlist\nq\n",
+    );
+    let out = stdout(&output);
+    assert!(out.contains("added 1\n"), "{}", out);
+    // rows are written the way the reminder is, not dumped
+    assert!(
+        out.contains("    remind cron \"0 9 * * 1-5\" \"standup\" warn 5m\n"),
+        "{}",
+        out
+    );
+    assert!(!out.contains("Cron {"), "{}", out);
+}
+
+#[test]
+fn debug_parser() {
+    let dir = temp("debug_parser");
+    let output = shell(
+        &dir,
+        &["debug", "parser"],
+        "let x = \"tea\"\nremind 9:00 \"${x}\"\nremind 25:00 \"x\"\n",
+    );
+    let out = stdout(&output);
+    assert!(out.contains("x = \"tea\"\n"), "{}", out);
+    assert!(
+        out.contains("Remind(") && out.contains("\"tea\""),
+        "{}",
+        out
+    );
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not between"));
+    // parsing doesnt touch the store or the shell's history
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test]
+fn history() {
+    let dir = temp("history");
+    let empty = remember(&dir, &["history"]);
+    assert!(empty.status.success());
+    assert_eq!(stdout(&empty), "nothing was shown\n");
+
+    let old = "2000-01-01T09:00:00+00:00\t3\treminder\tlibnotify\tok\t-\tlong ago\n";
+    let new = format!(
+        "{}\t-\twarning\tprint\terror no server\tsilenced\tjust now\n",
+        chrono::Local::now().to_rfc3339()
+    );
+    fs::create_dir_all(dir.join("remember")).unwrap();
+    fs::write(
+        dir.join("remember/notifications"),
+        format!("{}{}", old, new),
+    )
+    .unwrap();
+
+    let all = stdout(&remember(&dir, &["history"]));
+    assert_eq!(all.lines().count(), 2);
+    assert!(all.contains("   3  reminder  long ago"), "{}", all);
+    let recent = stdout(&remember(&dir, &["history", "--since", "1d"]));
+    assert_eq!(recent.lines().count(), 1);
+    assert!(
+        recent.ends_with("warning   just now (silenced) failed: no server\n"),
+        "{}",
+        recent
+    );
+    assert_eq!(
+        remember(&dir, &["history", "--since", "soon"])
+            .status
+            .code(),
+        Some(2)
+    );
+}
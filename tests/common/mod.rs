@@ -0,0 +1,9 @@
+use std::{env, fs, path::PathBuf, process};
+
+// an empty directory of its own for a test, the pid keeps runs apart
+pub fn temp(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("remember-test-{}-{}", process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Local, TimeZone};
+use remember::{cron::Cron, lexer::Lexer, parser, When};
+
+fn local(y: i32, mon: u32, d: u32, h: u32, m: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(y, mon, d, h, m, 0).unwrap()
+}
+
+fn next(expr: &str, after: DateTime<Local>) -> Option<DateTime<Local>> {
+    expr.parse::<Cron>().unwrap().next_fire_time(after)
+}
+
+#[test]
+fn weekdays() {
+    // 2022-05-13 is a friday
+    let after = local(2022, 5, 13, 9, 0);
+    assert_eq!(next("0 9 * * 1-5", after), Some(local(2022, 5, 16, 9, 0)));
+    assert_eq!(
+        next("0 9 * * mon-fri", after),
+        Some(local(2022, 5, 16, 9, 0))
+    );
+    assert_eq!(next("0 9 * * 7", after), Some(local(2022, 5, 15, 9, 0)));
+}
+
+#[test]
+fn steps_and_lists() {
+    let after = local(2022, 5, 13, 9, 7);
+    assert_eq!(next("*/15 * * * *", after), Some(local(2022, 5, 13, 9, 15)));
+    assert_eq!(
+        next("5,50 9-17/4 * * *", after),
+        Some(local(2022, 5, 13, 9, 50))
+    );
+    assert_eq!(
+        next("5 9-17/4 * * *", after),
+        Some(local(2022, 5, 13, 13, 5))
+    );
+}
+
+#[test]
+fn either_day() {
+    // the 1st or any monday, whichever comes first
+    let after = local(2022, 5, 13, 12, 0);
+    assert_eq!(next("0 0 1 * mon", after), Some(local(2022, 5, 16, 0, 0)));
+    // only mondays that are the 1st when the day of month starts with *
+    assert_eq!(next("0 0 */31 * mon", after), Some(local(2022, 8, 1, 0, 0)));
+}
+
+#[test]
+fn aliases() {
+    let after = local(2022, 5, 13, 12, 0);
+    assert_eq!(next("@daily", after), Some(local(2022, 5, 14, 0, 0)));
+    assert_eq!(next("@monthly", after), Some(local(2022, 6, 1, 0, 0)));
+    assert_eq!(next("@yearly", after), Some(local(2023, 1, 1, 0, 0)));
+}
+
+#[test]
+fn leap_day() {
+    let after = local(2022, 5, 13, 12, 0);
+    assert_eq!(
+        next("0 12 29 feb *", after),
+        Some(local(2024, 2, 29, 12, 0))
+    );
+    assert_eq!(next("0 12 30 feb *", after), None);
+}
+
+#[test]
+fn invalid() {
+    assert_eq!("0 9 * *".parse::<Cron>().unwrap_err().0, 0);
+    assert_eq!("0 24 * * *".parse::<Cron>().unwrap_err().0, 2);
+    assert_eq!("0 9 * * 1-9".parse::<Cron>().unwrap_err().0, 10);
+    assert_eq!("*/0 9 * * *".parse::<Cron>().unwrap_err().0, 2);
+    assert!("@reboot".parse::<Cron>().is_err());
+}
+
+#[test]
+fn statement() {
+    let line = "cron \"0 9 * * 1-5\"";
+    let mut lexer = Lexer::new(line.chars(), None);
+    let when = parser::parse_when(&mut lexer).unwrap();
+    assert_eq!(when, When::Cron("0 9 * * 1-5".parse().unwrap()));
+    assert_eq!(when.to_string(), line);
+
+    let mut lexer = Lexer::new("cron \"0 9 * * 1-9\"".chars(), None);
+    assert_eq!(parser::parse_when(&mut lexer).unwrap_err().loc().col, 17);
+}
@@ -0,0 +1,87 @@
+use remember::{csv, parser::ParserError, Reminder, When};
+
+#[test]
+fn quoting() {
+    let text = "summary,body,trigger,tags\n\
+\"standup, daily\",\"say \"\"hi\"\"\",9:00,+work @office\n\
+multi,\"line one\r\nline two\",mon 10:30,\n";
+    let parsed = csv::import(text, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    assert_eq!(parsed.reminders.len(), 2);
+
+    let standup = &parsed.reminders[0];
+    assert_eq!(standup.summary(), "standup, daily");
+    assert_eq!(standup.body(), Some("say \"hi\""));
+    assert_eq!(standup.when(), &When::Time(9, 0));
+    assert_eq!(standup.tags(), ["+work", "@office"]);
+
+    let multi = &parsed.reminders[1];
+    assert_eq!(multi.body(), Some("line one\r\nline two"));
+    assert_eq!(multi.when(), &When::Day(String::from("mon"), 10, 30));
+    assert!(multi.tags().is_empty());
+}
+
+#[test]
+fn header() {
+    // any order, any case, and columns that arent there are empty
+    let parsed = csv::import(
+        "Trigger, TAGS ,Summary\n16/5/2022 11:25,health,dentist\n",
+        None,
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let r = &parsed.reminders[0];
+    assert_eq!(r.summary(), "dentist");
+    assert_eq!(r.body(), None);
+    assert_eq!(r.when(), &When::Date(16, 5, 2022, 11, 25));
+    assert_eq!(r.tags(), ["+health"]);
+
+    // without a header the columns are summary,body,trigger,tags
+    let parsed = csv::import("stretch,,30\n\nwater,drink some,10:00,\n", None);
+    let whens: Vec<&When> = parsed.reminders.iter().map(|r| r.when()).collect();
+    assert_eq!(whens, [&When::Duration(30), &When::Time(10, 0)]);
+}
+
+#[test]
+fn errors() {
+    let text = "summary,body,trigger\n\
+\"spans\",\"two\nlines\",9:00\n\
+no trigger,,\n\
+bad,,25:00\n";
+    let parsed = csv::import(text, Some(String::from("tasks.csv")));
+    assert_eq!(parsed.reminders.len(), 1);
+    let rows: Vec<usize> = parsed.errors.iter().map(|e| e.loc().row).collect();
+    assert_eq!(rows, [4, 5]);
+    assert!(matches!(parsed.errors[0], ParserError::Unsupported(..)));
+    assert!(matches!(parsed.errors[1], ParserError::InvalidNum(..)));
+    assert!(parsed.errors[1].to_string().starts_with("tasks.csv:5:"));
+}
+
+#[test]
+fn round_trip() {
+    let mut reminders = vec![
+        Reminder::new(
+            When::Time(9, 0),
+            String::from("standup, \"daily\""),
+            Some(String::from("line one\nline two")),
+        ),
+        Reminder::new(When::Date(1, 0, 0, 9, 0), String::from("pay rent"), None),
+        Reminder::new(
+            When::Day(String::from("fri"), 17, 30),
+            String::from("timesheet"),
+            None,
+        ),
+    ];
+    reminders[0].set_tags(vec![String::from("+work"), String::from("@office")]);
+
+    let exported = csv::export(&reminders);
+    assert!(exported.starts_with("summary,body,trigger,tags\n"));
+    let parsed = csv::import(&exported, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    assert_eq!(parsed.reminders.len(), reminders.len());
+    for (got, want) in parsed.reminders.iter().zip(&reminders) {
+        assert_eq!(got.summary(), want.summary());
+        assert_eq!(got.body(), want.body());
+        assert_eq!(got.when(), want.when());
+        assert_eq!(got.tags(), want.tags());
+    }
+}
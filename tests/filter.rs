@@ -0,0 +1,108 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use remember::{
+    control,
+    filter::{self, Cmp, Filter, FilterError},
+    remind,
+    store::Store,
+    Reminder, When,
+};
+
+fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2022, 5, 13, 9, 30, 0).unwrap()
+}
+
+fn matches(text: &str, r: &Reminder) -> bool {
+    filter::parse(text).unwrap().matches(r, now())
+}
+
+#[test]
+fn parse() {
+    let tag = |t: &str| Box::new(Filter::Tag(String::from(t)));
+    assert_eq!(
+        filter::parse("tag:a or tag:b and not tag:c").unwrap(),
+        Filter::Or(
+            tag("a"),
+            Box::new(Filter::And(tag("b"), Box::new(Filter::Not(tag("c")))))
+        )
+    );
+    assert_eq!(
+        filter::parse("(tag:a or tag:b) and due<=2h").unwrap(),
+        Filter::And(
+            Box::new(Filter::Or(tag("a"), tag("b"))),
+            Box::new(Filter::Due(Cmp::LessEq, 7200))
+        )
+    );
+    assert_eq!(
+        filter::parse("TAG:+work").unwrap(),
+        Filter::Tag(String::from("+work"))
+    );
+    assert_eq!(
+        filter::parse("summary~\"deploy now\"").unwrap(),
+        Filter::Summary(String::from("deploy now"))
+    );
+    assert_eq!(
+        filter::parse("state:Done").unwrap(),
+        Filter::State(String::from("done"))
+    );
+}
+
+#[test]
+fn errors() {
+    let error = |text: &str| filter::parse(text).unwrap_err();
+    let e = error("tag:work and dur<7d");
+    assert!(matches!(e, FilterError::UnknownField(..)));
+    assert_eq!(e.loc().col, 14);
+    assert!(matches!(error("due<7x"), FilterError::Duration(..)));
+    assert!(matches!(error("due:7d"), FilterError::Expected(..)));
+    assert!(matches!(
+        error("priority>=urgent"),
+        FilterError::InvalidValue(..)
+    ));
+    assert!(matches!(error("(done"), FilterError::Expected(..)));
+    assert!(matches!(error("done active"), FilterError::Expected(..)));
+    assert!(matches!(error(""), FilterError::Expected(..)));
+    assert_eq!(
+        filter::diagnose("tag:work and dur<7d", &e),
+        "1:14: unknown field 'dur', expected tag/due/priority/summary/body/state\n  tag:work and dur<7d\n               ^"
+    );
+}
+
+#[test]
+fn matching() {
+    let deploy = remind!(10:00 "Deploy the API" "after lunch" +work !high);
+    let far = remind!(1/1/2100 9:00 "far" +work);
+    let mut done = Reminder::new(When::Time(9, 0), String::from("tea"), None);
+    done.set_done();
+
+    assert!(matches("tag:work and due<7d and not done", &deploy));
+    assert!(!matches("tag:work and due<7d and not done", &far));
+    assert!(!matches("tag:work and due<7d and not done", &done));
+    assert!(matches("due>52w or due<1m", &far));
+    assert!(matches("summary~deploy and body~LUNCH", &deploy));
+    assert!(!matches("body~lunch", &done));
+    assert!(matches("priority>normal", &deploy));
+    assert!(matches("priority:normal", &far));
+    assert!(matches("done", &done) && matches("active", &far));
+    assert!(!matches("due<52w", &done));
+}
+
+#[test]
+fn bulk() {
+    let mut store = Store::new(None);
+    store.add(remind!(10:00 "standup" +work));
+    store.add(remind!(11:00 "tea"));
+    store.add(remind!(12:00 "review" +work));
+    let now = now();
+    let mut handle = |line: &str| control::handle(&mut store, line, now);
+
+    assert_eq!(handle("snooze 1h where tag:work").unwrap(), "1 3");
+    assert_eq!(handle("list snoozed").unwrap().lines().count(), 2);
+    assert!(handle("snooze 1y where tag:work").is_err());
+    assert!(handle("rm where tag:").is_err());
+    assert_eq!(handle("rm where not tag:work").unwrap(), "2");
+    assert_eq!(handle("list").unwrap().lines().count(), 2);
+    assert_eq!(
+        store.get(1).unwrap().snoozed(),
+        Some(now + Duration::hours(1))
+    );
+}
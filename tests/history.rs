@@ -0,0 +1,103 @@
+mod common;
+
+use std::fs;
+
+use chrono::{Local, TimeZone};
+use common::temp;
+use remember::{
+    history::{Delivery, History, Kind},
+    output::{self, Format},
+};
+
+fn delivery(hour: u32, summary: &str) -> Delivery {
+    Delivery {
+        time: Local.with_ymd_and_hms(2022, 5, 13, hour, 0, 0).unwrap(),
+        id: Some(3),
+        kind: Kind::Reminder,
+        backend: String::from("libnotify"),
+        result: Ok(()),
+        action: None,
+        summary: summary.to_string(),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let path = temp("history").join("state/notifications");
+    let history = History::new(path.clone());
+    assert!(history.read(None).unwrap().is_empty());
+
+    let shown = delivery(9, "standup");
+    let failed = Delivery {
+        id: None,
+        kind: Kind::Warning,
+        result: Err(String::from("no\tserver")),
+        action: Some(String::from("silenced")),
+        ..delivery(10, "multi\nline")
+    };
+    let opened = Delivery {
+        kind: Kind::Action,
+        action: Some(String::from("opened https://example.com")),
+        ..delivery(11, "standup")
+    };
+    for d in [&shown, &failed, &opened] {
+        history.record(d).unwrap();
+    }
+    // the daemon was stopped part way through a line
+    let mut text = fs::read_to_string(&path).unwrap();
+    text.push_str("2022-05-13T12:00:00+00:00\t3\trem");
+    fs::write(&path, text).unwrap();
+
+    let read = history.read(None).unwrap();
+    assert_eq!(read.len(), 3);
+    assert_eq!(read[0], shown);
+    assert_eq!(read[1].result, Err(String::from("no server")));
+    assert_eq!(read[1].summary, "multi line");
+    assert_eq!(read[2], opened);
+
+    let since = Local.with_ymd_and_hms(2022, 5, 13, 10, 0, 0).unwrap();
+    let read = history.read(Some(since)).unwrap();
+    assert_eq!(read.len(), 2);
+    assert_eq!(read[0].kind, Kind::Warning);
+}
+
+#[test]
+fn formats() {
+    let deliveries = [
+        delivery(9, "standup"),
+        Delivery {
+            id: None,
+            result: Err(String::from("no server")),
+            action: Some(String::from("dropped")),
+            ..delivery(10, "say \"hi\"")
+        },
+    ];
+    let plain = output::history(&deliveries, Format::Plain);
+    let lines: Vec<&str> = plain.lines().collect();
+    assert_eq!(lines[0], "Fri 13/05 09:00     3  reminder  standup");
+    assert_eq!(
+        lines[1],
+        "Fri 13/05 10:00     -  reminder  say \"hi\" (dropped) failed: no server"
+    );
+
+    let json = output::history(&deliveries[1..], Format::Json);
+    assert!(
+        json.starts_with("[{\"time\":\"2022-05-13T10:00:00"),
+        "{}",
+        json
+    );
+    assert!(
+        json.ends_with(
+            "\"id\":null,\"kind\":\"reminder\",\"backend\":\"libnotify\",\"error\":\"no server\",\"action\":\"dropped\",\"summary\":\"say \\\"hi\\\"\"}]\n"
+        ),
+        "{}",
+        json
+    );
+
+    let tsv = output::history(&deliveries[..1], Format::Tsv);
+    assert!(
+        tsv.ends_with("\t3\treminder\tlibnotify\tok\t-\tstandup\n"),
+        "{}",
+        tsv
+    );
+}
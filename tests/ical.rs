@@ -0,0 +1,199 @@
+use chrono::{Datelike, Duration, Local, Timelike};
+use remember::{ical, parser::Parsed, Reminder, When};
+
+fn round_trip(when: When) -> Vec<Reminder> {
+    let reminder = Reminder::new(
+        when,
+        String::from("summary, with; escapes"),
+        Some(String::from("body")),
+    );
+    let exported = ical::export(&[reminder], Local::now());
+    let parsed = ical::import(&exported, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    parsed.reminders
+}
+
+fn assert_round_trip(when: When) {
+    let reminders = round_trip(when.clone());
+    assert_eq!(reminders.len(), 1);
+    assert_eq!(reminders[0].when(), &when);
+    assert_eq!(reminders[0].summary(), "summary, with; escapes");
+    assert_eq!(reminders[0].body(), Some("body"));
+}
+
+#[test]
+fn time() {
+    assert_round_trip(When::Time(10, 30));
+}
+
+#[test]
+fn day() {
+    assert_round_trip(When::Day(String::from("wed"), 9, 5));
+}
+
+#[test]
+fn date() {
+    assert_round_trip(When::Date(16, 5, 2022, 11, 25));
+}
+
+#[test]
+fn monthly_date() {
+    assert_round_trip(When::Date(1, 0, 0, 9, 0));
+}
+
+#[test]
+fn yearly_date() {
+    assert_round_trip(When::Date(29, 2, 0, 8, 0));
+}
+
+#[test]
+fn monthly_date_in_a_year() {
+    assert_round_trip(When::Date(15, 0, 2030, 12, 0));
+}
+
+#[test]
+fn duration_becomes_a_date() {
+    let reminders = round_trip(When::Duration(3600));
+    let expected = Local::now() + Duration::seconds(3600);
+    match reminders[0].when() {
+        When::Date(d, mon, y, h, m) => {
+            let got = (*d, *mon, *y, *h, *m);
+            let close = [expected, expected - Duration::minutes(1)]
+                .iter()
+                .any(|t| got == (t.day(), t.month(), t.year(), t.hour(), t.minute()));
+            assert!(close, "{:?} is not {}", got, expected);
+        }
+        other => panic!("expected a date, got {:?}", other),
+    }
+}
+
+#[test]
+fn alarm_before_a_weekly_event() {
+    let ics = "BEGIN:VCALENDAR\r\n\
+BEGIN:VEVENT\r\n\
+DTSTART:20220516T001000\r\n\
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE\r\n\
+SUMMARY:early\r\n\
+BEGIN:VALARM\r\n\
+TRIGGER:-PT15M\r\n\
+END:VALARM\r\n\
+END:VEVENT\r\n\
+END:VCALENDAR\r\n";
+    let parsed = ical::import(ics, None);
+    let whens: Vec<&When> = parsed.reminders.iter().map(|r| r.when()).collect();
+    assert_eq!(
+        whens,
+        [
+            &When::Day(String::from("sun"), 23, 55),
+            &When::Day(String::from("tue"), 23, 55)
+        ]
+    );
+}
+
+#[test]
+fn unsupported_rule_is_reported() {
+    let ics = "BEGIN:VEVENT\n\
+DTSTART:20220516T100000\n\
+RRULE:FREQ=DAILY;INTERVAL=2\n\
+BEGIN:VALARM\n\
+TRIGGER:PT0S\n\
+END:VALARM\n\
+END:VEVENT\n";
+    let parsed = ical::import(ics, Some(String::from("cal.ics")));
+    assert!(parsed.reminders.is_empty());
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].loc().row, 3);
+}
+
+#[test]
+fn zoned_day() {
+    assert_round_trip(When::Zoned(
+        Box::new(When::Day(String::from("mon"), 9, 0)),
+        "America/New_York".parse().unwrap(),
+    ));
+}
+
+#[test]
+fn cron() {
+    assert_round_trip(When::Cron(
+        "0,15,30,45 9,10,11,12,13,14,15,16,17 * * 1,2,3,4,5"
+            .parse()
+            .unwrap(),
+    ));
+}
+
+#[test]
+fn cron_on_either_day() {
+    let when = When::Cron("0 9 1,15 * 5".parse().unwrap());
+    let reminders = round_trip(when.clone());
+    assert_eq!(reminders.len(), 2);
+    let mut after = Local::now();
+    for _ in 0..20 {
+        let next = when.next_fire_time(after).unwrap();
+        let got = reminders
+            .iter()
+            .filter_map(|r| r.when().next_fire_time(after))
+            .min()
+            .unwrap();
+        assert_eq!(got, next);
+        after = next;
+    }
+}
+
+fn import_rule(rule: &str) -> Parsed {
+    let ics = format!(
+        "BEGIN:VEVENT\n\
+DTSTART:20220516T100000\n\
+RRULE:{}\n\
+BEGIN:VALARM\n\
+TRIGGER:PT0S\n\
+END:VALARM\n\
+END:VEVENT\n",
+        rule
+    );
+    ical::import(&ics, None)
+}
+
+#[test]
+fn yearly_months_and_days() {
+    let parsed = import_rule("FREQ=YEARLY;BYMONTH=3,6;BYMONTHDAY=1,15");
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let whens: Vec<&When> = parsed.reminders.iter().map(|r| r.when()).collect();
+    assert_eq!(
+        whens,
+        [
+            &When::Date(1, 3, 0, 10, 0),
+            &When::Date(15, 3, 0, 10, 0),
+            &When::Date(1, 6, 0, 10, 0),
+            &When::Date(15, 6, 0, 10, 0)
+        ]
+    );
+}
+
+#[test]
+fn monthly_days() {
+    let parsed = import_rule("FREQ=MONTHLY;BYMONTHDAY=1,15");
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let whens: Vec<&When> = parsed.reminders.iter().map(|r| r.when()).collect();
+    assert_eq!(
+        whens,
+        [&When::Date(1, 0, 0, 10, 0), &When::Date(15, 0, 0, 10, 0)]
+    );
+}
+
+#[test]
+fn rules_that_cant_be_kept_are_reported() {
+    for rule in [
+        "FREQ=DAILY;UNTIL=20220601T000000Z",
+        "FREQ=WEEKLY;BYDAY=MO;UNTIL=20220601",
+        "FREQ=YEARLY;BYMONTH=3;UNTIL=20300101",
+        "FREQ=MONTHLY;BYMONTHDAY=16;UNTIL=20220901",
+        "FREQ=WEEKLY;BYMONTH=3",
+        "FREQ=DAILY;BYDAY=MO",
+    ] {
+        let parsed = import_rule(rule);
+        assert!(parsed.reminders.is_empty(), "{}", rule);
+        assert_eq!(parsed.errors.len(), 1, "{}", rule);
+        assert_eq!(parsed.errors[0].loc().row, 3, "{}", rule);
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::fs;
+
+use common::temp;
+use remember::parser::{self, ParserError};
+
+fn summaries(parsed: &parser::Parsed) -> Vec<&str> {
+    parsed.reminders.iter().map(|r| r.summary()).collect()
+}
+
+#[test]
+fn include() {
+    let dir = temp("include");
+    fs::create_dir_all(dir.join("team")).unwrap();
+    fs::write(
+        dir.join("reminders"),
+        "remind 9:00 \"first\"\ninclude \"team/oncall\"\nremind 11:00 \"last\"\n",
+    )
+    .unwrap();
+    fs::write(dir.join("team/oncall"), "remind 10:00 \"oncall\"\n").unwrap();
+
+    let parsed = parser::parse_file(dir.join("reminders")).unwrap();
+    assert!(parsed.errors.is_empty());
+    assert_eq!(summaries(&parsed), ["first", "oncall", "last"]);
+    assert_eq!(parsed.files.len(), 2);
+
+    fs::write(dir.join("missing"), "include \"nowhere\"\n").unwrap();
+    let parsed = parser::parse_file(dir.join("missing")).unwrap();
+    assert!(matches!(
+        &parsed.errors[..],
+        [ParserError::IncludeFailed(loc, path, _)] if path == "nowhere" && loc.row == 1
+    ));
+}
+
+#[test]
+fn cycle() {
+    let dir = temp("include_cycle");
+    fs::write(dir.join("a"), "remind 9:00 \"a\"\ninclude \"b\"\n").unwrap();
+    fs::write(dir.join("b"), "remind 10:00 \"b\"\ninclude \"a\"\n").unwrap();
+
+    let parsed = parser::parse_file(dir.join("a")).unwrap();
+    assert_eq!(summaries(&parsed), ["a", "b"]);
+    assert!(matches!(
+        &parsed.errors[..],
+        [ParserError::IncludeCycle(loc, path)] if path == "a" && loc.row == 2
+    ));
+
+    // a file included twice without a loop is loaded once and isnt an error
+    fs::write(dir.join("c"), "include \"b\"\ninclude \"b\"\n").unwrap();
+    fs::write(dir.join("b"), "remind 10:00 \"b\"\n").unwrap();
+    let parsed = parser::parse_file(dir.join("c")).unwrap();
+    assert!(parsed.errors.is_empty());
+    assert_eq!(summaries(&parsed), ["b"]);
+}
+
+#[test]
+fn conf_d() {
+    let dir = temp("conf_d");
+    let conf = dir.join("conf.d");
+    fs::create_dir_all(&conf).unwrap();
+    fs::write(dir.join("reminders"), "remind 8:00 \"main\"\n").unwrap();
+    fs::write(conf.join("20-work"), "remind 10:00 \"work\"\n").unwrap();
+    fs::write(conf.join("10-home"), "remind 9:00 \"home\"\n").unwrap();
+    fs::write(conf.join(".hidden"), "remind 9:00 \"hidden\"\n").unwrap();
+    fs::write(conf.join("20-work~"), "remind 9:00 \"backup\"\n").unwrap();
+
+    let parsed = parser::parse_paths(&[dir.join("reminders"), conf.clone()]).unwrap();
+    assert!(parsed.errors.is_empty());
+    assert_eq!(summaries(&parsed), ["main", "home", "work"]);
+
+    // a file that cant be read is reported and the rest still load
+    fs::write(conf.join("15-broken"), [0xff, 0xfe, b'\n']).unwrap();
+    let parsed = parser::parse_paths(&[conf]).unwrap();
+    assert_eq!(summaries(&parsed), ["home", "work"]);
+    assert_eq!(parsed.errors.len(), 1);
+    assert!(matches!(&parsed.errors[0], ParserError::ReadFailed(..)));
+    assert!(parsed.errors[0].to_string().contains("15-broken"));
+}
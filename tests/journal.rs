@@ -0,0 +1,176 @@
+mod common;
+
+use std::fs;
+
+use chrono::{Local, TimeZone};
+use common::temp;
+use remember::{
+    control::{self, Control},
+    store::Store,
+};
+
+fn summaries(store: &Store) -> Vec<(u32, String, bool)> {
+    store
+        .iter()
+        .map(|(id, r)| (id, r.summary().to_string(), r.is_done()))
+        .collect()
+}
+
+#[test]
+fn undo_redo() {
+    let mut store = Store::new(None);
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut request = |line: &str| control::handle(&mut store, line, now);
+
+    assert_eq!(request("undo").unwrap_err(), "nothing to undo");
+    request("add remind 10:00 \"a\"").unwrap();
+    request("add remind 11:00 \"b\" +work").unwrap();
+    request("edit 1 remind 10:30 \"a later\"").unwrap();
+    request("done 2").unwrap();
+    request("rm 1").unwrap();
+    // a request that fails or changes nothing is not journaled
+    assert!(request("rm 9").is_err());
+    request("rm where tag:nothing").unwrap();
+    request("list").unwrap();
+
+    assert_eq!(request("undo").unwrap(), "rm 1");
+    assert_eq!(request("undo").unwrap(), "done 2");
+    assert_eq!(request("undo").unwrap(), "edit 1 remind 10:30 \"a later\"");
+    let journal = request("journal").unwrap();
+    let lines: Vec<&str> = journal.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "undo\tadd remind 11:00 \"b\" +work",
+            "undo\tadd remind 10:00 \"a\"",
+            "redo\tedit 1 remind 10:30 \"a later\"",
+            "redo\tdone 2",
+            "redo\trm 1",
+        ]
+    );
+    assert_eq!(request("redo").unwrap(), "edit 1 remind 10:30 \"a later\"");
+    assert_eq!(
+        summaries(&store),
+        [
+            (1, String::from("a later"), false),
+            (2, String::from("b"), false)
+        ]
+    );
+
+    // a new change throws away what could have been redone
+    control::handle(&mut store, "snooze 2 1h", now).unwrap();
+    assert_eq!(
+        control::handle(&mut store, "redo", now).unwrap_err(),
+        "nothing to redo"
+    );
+    assert_eq!(
+        control::handle(&mut store, "undo", now).unwrap(),
+        "snooze 2 1h"
+    );
+    assert_eq!(store.get(2).unwrap().snoozed(), None);
+}
+
+#[test]
+fn bulk_is_one_step() {
+    let mut store = Store::new(None);
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut request = |line: &str| control::handle(&mut store, line, now);
+
+    request("add remind 10:00 \"a\" +work").unwrap();
+    request("add remind 11:00 \"b\" +work").unwrap();
+    request("add remind 12:00 \"c\"").unwrap();
+    assert_eq!(request("rm where tag:work").unwrap(), "1 2");
+    assert_eq!(request("undo").unwrap(), "rm where tag:work");
+    let ids: Vec<u32> = store.iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, [1, 2, 3]);
+
+    // undoing an add doesnt hand its id out again
+    control::handle(&mut store, "undo", now).unwrap();
+    assert_eq!(
+        control::handle(&mut store, "add remind 9:00 \"d\"", now).unwrap(),
+        "4"
+    );
+}
+
+#[test]
+fn persists() {
+    let path = temp("journal").join("store");
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut store = Store::open(path.clone()).unwrap();
+    control::handle(&mut store, "add remind 10:00 \"a\" \"body\"", now).unwrap();
+    control::handle(&mut store, "done 1", now).unwrap();
+    control::handle(&mut store, "rm 1", now).unwrap();
+    control::handle(&mut store, "undo", now).unwrap();
+    assert!(path.with_extension("journal").exists());
+
+    // another client undoes what this one did
+    let mut store = Store::open(path.clone()).unwrap();
+    assert!(store.get(1).unwrap().is_done());
+    assert_eq!(control::handle(&mut store, "undo", now).unwrap(), "done 1");
+    let mut store = Store::open(path.clone()).unwrap();
+    assert!(!store.get(1).unwrap().is_done());
+    assert_eq!(store.get(1).unwrap().body(), Some("body"));
+    assert_eq!(control::handle(&mut store, "redo", now).unwrap(), "done 1");
+    assert_eq!(control::handle(&mut store, "redo", now).unwrap(), "rm 1");
+    let store = Store::open(path.clone()).unwrap();
+    assert!(store.is_empty());
+
+    fs::write(path.with_extension("journal"), "undone 0\nafter 1 -\n").unwrap();
+    assert!(Store::open(path)
+        .unwrap_err()
+        .to_string()
+        .contains("store.journal:2: corrupt store"));
+}
+
+#[test]
+fn written_when_it_changes() {
+    let path = temp("journal_writes").join("store");
+    let journal = path.with_extension("journal");
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut store = Store::open(path.clone()).unwrap();
+    store.save().unwrap();
+    assert!(!journal.exists());
+
+    control::handle(&mut store, "add remind 10:00 \"a\"", now).unwrap();
+    assert!(journal.exists());
+    // saves that dont touch the journal leave it be, the daemon saves every time one goes off
+    fs::remove_file(&journal).unwrap();
+    store.get_mut(1).unwrap().set_done();
+    store.save().unwrap();
+    control::handle(&mut store, "rm 9", now).unwrap_err();
+    assert!(!journal.exists());
+
+    control::handle(&mut store, "undo", now).unwrap();
+    assert_eq!(
+        fs::read_to_string(&journal).unwrap().lines().nth(1),
+        Some("undone 1")
+    );
+    fs::remove_file(&journal).unwrap();
+    control::handle(&mut store, "redo", now).unwrap();
+    assert!(fs::read_to_string(&journal).unwrap().contains("undone 0\n"));
+}
+
+#[test]
+fn clients_share_it() {
+    let dir = temp("journal_clients");
+    let socket = dir.join("remember.sock");
+    let connect = || Control::connect(socket.clone(), dir.join("store")).unwrap();
+    let (mut a, mut b) = (connect(), connect());
+    a.request("add remind 10:00 \"a\"").unwrap();
+    b.request("add remind 11:00 \"b\"").unwrap();
+    a.request("done 2").unwrap();
+
+    // the newest change is undone whichever client made it, and neither loses the others
+    assert_eq!(b.request("undo").unwrap(), "done 2");
+    assert_eq!(a.request("undo").unwrap(), "add remind 11:00 \"b\"");
+    assert_eq!(
+        b.request("journal").unwrap(),
+        "undo\tadd remind 10:00 \"a\"\nredo\tadd remind 11:00 \"b\"\nredo\tdone 2"
+    );
+    assert_eq!(b.request("redo").unwrap(), "add remind 11:00 \"b\"");
+    let store = Store::open(dir.join("store")).unwrap();
+    assert_eq!(
+        summaries(&store),
+        [(1, String::from("a"), false), (2, String::from("b"), false)]
+    );
+}
@@ -0,0 +1,63 @@
+use remember::{links, notify::Notice, Reminder, When};
+
+#[test]
+fn find() {
+    assert_eq!(links::find("<https://google.com>"), ["https://google.com"]);
+    assert_eq!(
+        links::find("notes at https://example.com/a?b=c#d, or http://x.org."),
+        ["https://example.com/a?b=c#d", "http://x.org"]
+    );
+    assert_eq!(
+        links::find("mail mailto:me@example.com"),
+        ["mailto:me@example.com"]
+    );
+    assert_eq!(links::find("HTTPS://EXAMPLE.COM"), ["HTTPS://EXAMPLE.COM"]);
+    assert_eq!(
+        links::find("'file:///tmp/notes.txt'"),
+        ["file:///tmp/notes.txt"]
+    );
+}
+
+#[test]
+fn not_links() {
+    assert!(links::find("no links here").is_empty());
+    assert!(links::find("https://").is_empty());
+    assert!(links::find("xhttps://example.com").is_empty());
+    assert!(links::find("").is_empty());
+}
+
+#[test]
+fn brackets() {
+    assert_eq!(
+        links::find("(see https://en.wikipedia.org/wiki/Rust_(programming_language))"),
+        ["https://en.wikipedia.org/wiki/Rust_(programming_language)"]
+    );
+    assert_eq!(
+        links::find("(https://example.com)"),
+        ["https://example.com"]
+    );
+    assert_eq!(
+        links::find("[https://example.com]"),
+        ["https://example.com"]
+    );
+}
+
+#[test]
+fn hyperlink() {
+    assert_eq!(
+        links::hyperlink("go to <https://ex.com> now"),
+        "go to <\x1b]8;;https://ex.com\x1b\\https://ex.com\x1b]8;;\x1b\\> now"
+    );
+    assert_eq!(links::hyperlink("plain, ünïcode"), "plain, ünïcode");
+}
+
+#[test]
+fn notice() {
+    let r = Reminder::new(
+        When::Time(9, 0),
+        String::from("https://not.the.body"),
+        Some(String::from("standup https://meet.example.com/abc")),
+    );
+    let notice: Notice = r.notice(None, chrono::Local::now(), 1);
+    assert_eq!(notice.urls, ["https://meet.example.com/abc"]);
+}
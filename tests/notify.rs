@@ -0,0 +1,85 @@
+use remember::{
+    lexer::Lexer,
+    notify::{Backend, Notice, Urgency},
+    parser,
+    quiet::Action,
+    remind, Reminder, When,
+};
+
+// keeps every notice instead of showing it
+#[derive(Default)]
+struct Record(Vec<(String, Notice)>);
+
+impl Backend for Record {
+    fn show(&mut self, id: &str, notice: &Notice) -> Result<(), String> {
+        self.0.push((id.to_string(), notice.clone()));
+        Ok(())
+    }
+}
+
+fn parse(line: &str) -> Result<Reminder, parser::ParserError> {
+    let mut lexer = Lexer::new(line.chars(), None);
+    parser::get_command(&mut lexer);
+    parser::parse_remind(&mut lexer, &parser::Vars::new())
+}
+
+#[test]
+fn statement() {
+    let r = parse(
+        "remind 9:00 \"standup\" category \"work\" timeout 30s icon \"clock\" urgency critical",
+    )
+    .unwrap();
+    assert_eq!(r.urgency(), Urgency::Critical);
+    assert_eq!(r.icon(), Some("clock"));
+    assert_eq!(r.timeout(), Some(30));
+    assert_eq!(r.category(), Some("work"));
+    assert_eq!(
+        r.to_string(),
+        "remind 9:00 \"standup\" urgency critical icon \"clock\" timeout 30s category \"work\""
+    );
+    assert_eq!(parse(&r.to_string()).unwrap().to_string(), r.to_string());
+
+    let r = parse("remind 9:00 \"x\" timeout never warn 5m").unwrap();
+    assert_eq!(r.timeout(), Some(0));
+    assert_eq!(r.to_string(), "remind 9:00 \"x\" timeout never warn 5m");
+
+    let e = parse("remind 9:00 \"x\" urgency loud").unwrap_err();
+    assert!(matches!(e, parser::ParserError::InvalidUrgency(..)));
+    assert_eq!(e.loc().col, 25);
+}
+
+#[test]
+fn macro_options() {
+    let r = remind!(9:00 "standup" "daily" urgency low timeout never warn 5m, 1m category "work");
+    assert_eq!(r.body(), Some("daily"));
+    assert_eq!(r.urgency(), Urgency::Low);
+    assert_eq!(r.timeout(), Some(0));
+    assert_eq!(r.warn(), &[300, 60]);
+    assert_eq!(r.category(), Some("work"));
+    assert_eq!(r.icon(), None);
+}
+
+#[test]
+fn backend() {
+    let mut r = Reminder::new(When::Duration(0), String::from("tea"), None);
+    r.set_urgency(Urgency::Critical);
+    r.set_icon(Some(String::from("cup")));
+    r.set_timeout(Some(10));
+    let mut record = Record::default();
+
+    r.show_warning(&mut record, 60).unwrap();
+    r.deliver(&mut record, Some(4), None, Some(Action::Silent))
+        .unwrap();
+    r.deliver(&mut record, Some(4), None, Some(Action::Defer))
+        .unwrap();
+    assert_eq!(record.0.len(), 2);
+    assert_eq!(record.0[0].0, r.key());
+    assert_eq!(record.0[0].1.summary, "in 1 minute: tea");
+    assert_eq!(record.0[0].1.urgency, Urgency::Critical);
+    assert_eq!(record.0[1].1.summary, "tea");
+    assert_eq!(record.0[1].1.urgency, Urgency::Low);
+    assert_eq!(record.0[1].1.icon.as_deref(), Some("cup"));
+    assert_eq!(record.0[1].1.timeout, Some(10));
+    assert_eq!(record.0[0].1.id, None);
+    assert_eq!(record.0[1].1.id, Some(4));
+}
@@ -0,0 +1,86 @@
+use chrono::{DateTime, Local, TimeZone};
+use remember::{
+    output::{self, Entry, Format},
+    remind, Reminder, When,
+};
+
+fn now() -> DateTime<Local> {
+    Local.with_ymd_and_hms(2022, 5, 13, 9, 30, 0).unwrap()
+}
+
+#[test]
+fn format() {
+    assert_eq!("json".parse(), Ok(Format::Json));
+    assert_eq!("tsv".parse(), Ok(Format::Tsv));
+    assert_eq!("plain".parse(), Ok(Format::Plain));
+    assert!("xml".parse::<Format>().is_err());
+}
+
+#[test]
+fn json() {
+    assert_eq!(output::string("say \"hi\"\n\t\\"), r#""say \"hi\"\n\t\\""#);
+    assert_eq!(output::string("\u{1}"), r#""\u0001""#);
+
+    let r = remind!(mon 10:30 "plan" "the \"week\"");
+    let e = Entry {
+        id: Some(3),
+        reminder: &r,
+    };
+    let next = Local.with_ymd_and_hms(2022, 5, 16, 10, 30, 0).unwrap();
+    assert_eq!(
+        output::json(&e, now()),
+        format!(
+            r#"{{"id":3,"summary":"plan","body":"the \"week\"","trigger":"mon 10:30","next":"{}","state":"active","tags":[],"priority":"normal"}}"#,
+            next.to_rfc3339()
+        )
+    );
+
+    let mut done = Reminder::new(When::Date(1, 1, 2100, 9, 0), String::from("x"), None);
+    done.set_done();
+    let e = Entry {
+        id: None,
+        reminder: &done,
+    };
+    assert_eq!(
+        output::json(&e, now()),
+        r#"{"id":null,"summary":"x","body":null,"trigger":"1/1/2100 9:00","next":null,"state":"done","tags":[],"priority":"normal"}"#
+    );
+}
+
+#[test]
+fn list_and_next() {
+    let daily = remind!(9:00 "standup" "a\tb" +work @office !high);
+    let weekly = remind!(mon 10:30 "plan");
+    let entries = [
+        Entry {
+            id: Some(1),
+            reminder: &weekly,
+        },
+        Entry {
+            id: None,
+            reminder: &daily,
+        },
+    ];
+    let tsv = output::list(&entries, Format::Tsv, now());
+    let lines: Vec<Vec<&str>> = tsv.lines().map(|l| l.split('\t').collect()).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0][0], "-");
+    assert_eq!(
+        lines[0][2..],
+        ["active", "standup", "a b", "9:00", "+work @office", "high"]
+    );
+    assert_eq!(lines[1][0], "1");
+    assert_eq!(lines[1][6..], ["", "normal"]);
+
+    assert_eq!(
+        output::next_due(&entries, Format::Plain, now()),
+        "09:00 standup (in 23h)\n"
+    );
+    assert_eq!(output::next_due(&[], Format::Json, now()), "null\n");
+    assert_eq!(output::next_due(&[], Format::Plain, now()), "");
+
+    let json = output::agenda(&entries, 4, Format::Json, now());
+    assert!(json.starts_with("[{\"at\":"));
+    assert_eq!(json.matches("\"id\":1").count(), 1);
+    assert_eq!(json.matches("\"id\":null").count(), 4);
+}
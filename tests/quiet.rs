@@ -0,0 +1,72 @@
+use chrono::{DateTime, Local, TimeZone};
+use remember::{
+    lexer::Lexer,
+    parser::{self, Statement, Vars},
+    quiet::{Action, Quiet, Window},
+    Reminder, When,
+};
+
+// 2022-05-13 is a friday
+fn local(d: u32, h: u32, m: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2022, 5, d, h, m, 0).unwrap()
+}
+
+fn window(line: &str) -> Window {
+    let mut lexer = Lexer::new(line.chars(), None);
+    match parser::parse_statement(&mut lexer, &Vars::new()) {
+        Ok(Some(Statement::Quiet(window))) => window,
+        Ok(_) => panic!("not a quiet statement"),
+        Err(e) => panic!("{}", e),
+    }
+}
+
+fn tagged(tag: &str) -> Reminder {
+    let mut r = Reminder::new(When::Time(9, 0), String::from("x"), None);
+    r.set_tags(vec![String::from(tag)]);
+    r
+}
+
+#[test]
+fn overnight() {
+    let w = window("quiet 22:00 7:00");
+    assert_eq!(w.action, Action::Defer);
+    assert!(w.contains(local(13, 23, 0)));
+    assert!(w.contains(local(14, 6, 59)));
+    assert!(!w.contains(local(14, 7, 0)));
+    assert!(!w.contains(local(13, 21, 59)));
+}
+
+#[test]
+fn weekend() {
+    let w = window("quiet sat, sun 0:00 0:00 drop");
+    assert_eq!(w.action, Action::Drop);
+    assert!(!w.contains(local(13, 23, 59)));
+    assert!(w.contains(local(14, 0, 0)));
+    assert!(w.contains(local(15, 23, 59)));
+    assert!(!w.contains(local(16, 0, 0)));
+}
+
+#[test]
+fn tags_and_urgent() {
+    let quiet = Quiet {
+        windows: vec![window("quiet 9:00 17:00 silent tag \"home\"")],
+        urgent: vec![String::from("oncall")],
+    };
+    let now = local(13, 10, 0);
+    assert_eq!(quiet.action(&tagged("home"), now), Some(Action::Silent));
+    assert_eq!(quiet.action(&tagged("work"), now), None);
+
+    let quiet = Quiet {
+        windows: vec![window("quiet 9:00 17:00")],
+        urgent: vec![String::from("oncall")],
+    };
+    assert_eq!(quiet.action(&tagged("work"), now), Some(Action::Defer));
+    assert_eq!(quiet.action(&tagged("oncall"), now), None);
+}
+
+#[test]
+fn invalid() {
+    let mut lexer = Lexer::new("quiet 22:00 7:00 later".chars(), None);
+    let e = parser::parse_statement(&mut lexer, &Vars::new());
+    assert!(matches!(e, Err(parser::ParserError::InvalidQuiet(..))));
+}
@@ -0,0 +1,51 @@
+use remember::{
+    lexer::{Lexer, TokenKind},
+    parser, reminder, Reminder, When,
+};
+
+fn strings(line: &str) -> Vec<(TokenKind, String)> {
+    Lexer::new(line.chars(), None)
+        .map(|t| (t.kind, t.text))
+        .take_while(|(kind, _)| *kind != TokenKind::End)
+        .collect()
+}
+
+#[test]
+fn escapes() {
+    assert_eq!(
+        strings(r#""it's \"x\"" 'a\'b' "C:\dir\\""#),
+        [
+            (TokenKind::Str, String::from("it's \"x\"")),
+            (TokenKind::Str, String::from("a'b")),
+            (TokenKind::Str, String::from("C:\\dir\\")),
+        ]
+    );
+    assert_eq!(strings(r#""open\""#)[0].0, TokenKind::UnclosedStr);
+}
+
+#[test]
+fn round_trip() {
+    for text in [
+        "it's \"x\"",
+        "say \"hi\"",
+        "it's",
+        "C:\\dir\\",
+        "a\\\\b \\\"",
+        "\\",
+        "two\nlines",
+        "not\\n a new line",
+    ] {
+        let mut r = Reminder::new(When::Duration(60), text.to_string(), Some(text.to_string()));
+        r.set_icon(Some(text.to_string()));
+        r.set_category(Some(text.to_string()));
+        let again = parser::parse_reminder(&r.to_string()).unwrap();
+        assert_eq!(again.summary(), text, "{}", r);
+        assert_eq!(again.body(), Some(text));
+        assert_eq!(again.icon(), Some(text));
+        assert_eq!(again.category(), Some(text));
+    }
+    // text that doesnt need escapes is written as it always was
+    assert_eq!(reminder::quote("say \"hi\""), "'say \"hi\"'");
+    assert_eq!(reminder::quote("C:\\dir"), "\"C:\\dir\"");
+    assert_eq!(reminder::quote("it's \"x\""), "\"it's \\\"x\\\"\"");
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use std::fs;
+
+use chrono::{Local, TimeZone};
+use common::temp;
+use remember::{parser, reminder, Reminder};
+
+fn parse(line: &str) -> Reminder {
+    parser::parse_reminder(line).unwrap()
+}
+
+#[test]
+fn merge_keeps_state() {
+    let created = Local.with_ymd_and_hms(2022, 5, 13, 8, 0, 0).unwrap();
+    let last = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut shown = parse("remind 9:00 \"standup\"");
+    shown.restore(created, Some(last), None, None, 3, true);
+    let mut edited = parse("remind 10:00 \"review\"");
+    edited.restore(created, Some(last), None, None, 2, true);
+
+    let merged = reminder::merge(
+        vec![shown, edited],
+        vec![
+            parse("remind 10:30 \"review\""),
+            parse("remind 9:00 \"standup\""),
+            parse("remind 11:00 \"lunch\""),
+        ],
+    );
+    let summaries: Vec<&str> = merged.iter().map(|r| r.summary()).collect();
+    assert_eq!(summaries, ["review", "standup", "lunch"]);
+
+    // unchanged, so the one that was live is kept along with what it went through
+    assert_eq!(merged[1].created(), created);
+    assert_eq!(merged[1].last(), Some(last));
+    assert_eq!(merged[1].count(), 3);
+    assert!(merged[1].is_done());
+
+    // changed or new, so they start over
+    for r in [&merged[0], &merged[2]] {
+        assert_eq!(r.last(), None);
+        assert_eq!(r.count(), 0);
+        assert!(!r.is_done());
+    }
+}
+
+#[test]
+fn merge_duplicates() {
+    let mut done = parse("remind 9:00 \"water\"");
+    done.set_done();
+    let merged = reminder::merge(
+        vec![done, parse("remind 9:00 \"water\"")],
+        vec![
+            parse("remind 9:00 \"water\""),
+            parse("remind 9:00 \"water\""),
+            parse("remind 9:00 \"water\""),
+        ],
+    );
+    // each live reminder is only kept once
+    let done: Vec<bool> = merged.iter().map(|r| r.is_done()).collect();
+    assert_eq!(done, [true, false, false]);
+}
+
+#[test]
+fn reload_with_errors() {
+    let path = temp("reload").join("reminders");
+    fs::write(&path, "remind 9:00 \"standup\"\nremind 10:00 \"review\"\n").unwrap();
+    let parsed = parser::reload(&[&path]).unwrap().unwrap();
+    assert_eq!(parsed.reminders.len(), 2);
+
+    // a half written file isnt loaded at all, not even the lines that parse
+    fs::write(&path, "remind 9:00 \"standup\"\nremind 25:00 \"review\"\n").unwrap();
+    let Err(errors) = parser::reload(&[&path]).unwrap() else {
+        panic!("a reload with errors was kept");
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].loc().row, 2);
+
+    fs::remove_file(&path).unwrap();
+    assert!(parser::reload(&[&path]).is_err());
+}
@@ -0,0 +1,151 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use remember::{
+    parser::{self, ParserError},
+    remind, When,
+};
+
+fn whens(text: &str) -> Vec<When> {
+    let parsed = remind::import(text, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    parsed.reminders.iter().map(|r| r.when().clone()).collect()
+}
+
+// the column and message of the one error in text
+fn error(text: &str) -> (usize, String) {
+    let parsed = remind::import(text, None);
+    assert!(parsed.reminders.is_empty());
+    match &parsed.errors[..] {
+        [e @ ParserError::Unsupported(loc, _)] => (loc.col, e.to_string()),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn dates() {
+    assert_eq!(
+        whens("REM 16 May 2022 AT 11:25 MSG dentist"),
+        [When::Date(16, 5, 2022, 11, 25)]
+    );
+    assert_eq!(
+        whens("rem 2022-05-16 at 8.30pm msg x"),
+        [When::Date(16, 5, 2022, 20, 30)]
+    );
+    // without a month or year it matches any, without AT it is at 9:00
+    assert_eq!(whens("REM 1 MSG rent"), [When::Date(1, 0, 0, 9, 0)]);
+    assert_eq!(whens("REM 29 Feb MSG leap"), [When::Date(29, 2, 0, 9, 0)]);
+}
+
+#[test]
+fn weekdays_and_repeats() {
+    assert_eq!(
+        whens("REM Mon Wednesday AT 5:00pm MSG gym"),
+        [
+            When::Day(String::from("mon"), 17, 0),
+            When::Day(String::from("wed"), 17, 0)
+        ]
+    );
+    assert_eq!(whens("REM AT 12:15am MSG midnight"), [When::Time(0, 15)]);
+    // 2022-05-16 is a monday
+    assert_eq!(
+        whens("REM 2022-05-16 *7 AT 10:00 MSG weekly\nREM 16 May 2022 *1 MSG daily"),
+        [When::Day(String::from("mon"), 10, 0), When::Time(9, 0)]
+    );
+}
+
+#[test]
+fn message_tags_and_warnings() {
+    let text = "# a comment\n\
+; another\n\
+\n\
+REM Fri ++2 TAG work \\\n    AT 17:00 MSG %\"timesheet%\" 100%% done\n";
+    let parsed = remind::import(text, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    let r = &parsed.reminders[0];
+    assert_eq!(r.summary(), "timesheet 100% done");
+    assert_eq!(r.when(), &When::Day(String::from("fri"), 17, 0));
+    assert_eq!(r.tags(), ["+work"]);
+    assert_eq!(r.warn(), [2 * 24 * 60 * 60, 24 * 60 * 60]);
+}
+
+#[test]
+fn repeats_start_on_their_date() {
+    let parsed = remind::import("REM 2030-05-16 *1 AT 10:00 MSG daily", None);
+    let r = &parsed.reminders[0];
+    assert_eq!(r.from(), NaiveDate::from_ymd_opt(2030, 5, 16));
+    let now = Local.with_ymd_and_hms(2030, 5, 1, 12, 0, 0).unwrap();
+    let first = Local.with_ymd_and_hms(2030, 5, 16, 10, 0, 0).unwrap();
+    assert_eq!(r.next_fire_time(now), Some(first));
+    // and after it every day as before
+    assert_eq!(
+        r.next_fire_time(first),
+        Some(Local.with_ymd_and_hms(2030, 5, 17, 10, 0, 0).unwrap())
+    );
+    // the date is kept in the statement it is printed as
+    let again = parser::parse_reminder(&r.to_string()).unwrap();
+    assert_eq!(again.from(), r.from());
+    assert_eq!(again.next_fire_time(now), Some(first));
+}
+
+#[test]
+fn new_lines() {
+    let parsed = remind::import("REM Mon MSG standup%_bring notes", None);
+    let r = &parsed.reminders[0];
+    assert_eq!(r.summary(), "standup\nbring notes");
+    let again = parser::parse_reminder(&r.to_string()).unwrap();
+    assert_eq!(again.summary(), "standup\nbring notes");
+}
+
+#[test]
+fn unsupported() {
+    assert_eq!(
+        error("SET x 1"),
+        (1, String::from("1:1: unsupported command 'SET'"))
+    );
+    assert_eq!(
+        error("REM Mon"),
+        (1, String::from("1:1: unsupported REM without a MSG"))
+    );
+    assert_eq!(
+        error("REM Mon MSG"),
+        (9, String::from("1:9: unsupported empty MSG"))
+    );
+    assert_eq!(error("REM Mon SCHED f MSG x").0, 9);
+    assert_eq!(error("REM Mon AT MSG x").0, 12);
+    assert_eq!(error("REM Mon AT 25:00 MSG x").0, 12);
+    assert_eq!(error("REM Mon AT 9:00 +1 MSG x").0, 17);
+    assert_eq!(error("REM Mon AT 9:00 *1 MSG x").0, 17);
+    assert_eq!(
+        error("REM 16 May 2022 *3 MSG x").1,
+        "1:1: unsupported repeat every 3 days, only *1 and *7 are"
+    );
+    assert!(error("REM 16 *7 MSG x")
+        .1
+        .ends_with("repeat without a full date"));
+    assert!(error("REM May MSG x")
+        .1
+        .ends_with("month or year without a day"));
+    assert!(error("REM 16 Mon MSG x")
+        .1
+        .ends_with("weekday together with a date"));
+    assert!(error("REM 30 Feb 2022 MSG x").1.contains("it doesnt exist"));
+    assert!(error("REM 3 4 MSG x").1.contains("day given twice"));
+}
+
+#[test]
+fn columns_count_characters() {
+    // é is two bytes but one column
+    assert_eq!(
+        error("REM Mon TAG café MSG hi %a"),
+        (25, String::from("1:25: unsupported substitution '%a'"))
+    );
+    let parsed = remind::import("REM Mon TAG café MSG naïve", None);
+    assert_eq!(parsed.reminders[0].summary(), "naïve");
+
+    // a line continued from the one before is reported on the line it starts on
+    let parsed = remind::import(
+        "REM 1 MSG fine\nREM Mon \\\n  BOGUS MSG x\n",
+        Some(String::from("r")),
+    );
+    assert_eq!(parsed.errors.len(), 1);
+    assert_eq!(parsed.errors[0].loc().row, 2);
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "serde")]
+
+use chrono::{Local, TimeZone};
+use remember::{parser, Reminder, When};
+
+fn round_trip(r: &Reminder) -> Reminder {
+    let json = serde_json::to_string(r).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn reminders() {
+    let created = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let last = Local.with_ymd_and_hms(2022, 5, 16, 9, 0, 0).unwrap();
+    for line in [
+        "remind 30 \"stretch\"",
+        "remind mon 9:00 tz \"America/New_York\" \"sync\" \"notes\" +work !high warn 1d, 15m",
+        "remind 16/5/2022 11:25 tz \"Europe/London\" \"dentist\" urgency critical timeout never",
+        "remind cron \"*/15 9-17 1,15 * 1-5\" \"standup\" icon \"clock\" category \"work\"",
+        "remind 7:30 \"gym\" from 1/6/2024",
+    ] {
+        let mut r = parser::parse_reminder(line).unwrap();
+        r.restore(created, Some(last), None, Some(last), 3, false);
+        let again = round_trip(&r);
+        assert_eq!(again.to_string(), r.to_string());
+        assert_eq!(again.when(), r.when());
+        assert_eq!(again.created(), created);
+        assert_eq!(again.last(), Some(last));
+        assert_eq!(again.warned(), Some(last));
+        assert_eq!(again.count(), 3);
+        assert_eq!(again.next_fire_time(last), r.next_fire_time(last));
+    }
+}
+
+#[test]
+fn zoned_cron() {
+    let r = parser::parse_reminder("remind cron \"0 9 * * *\" \"x\"").unwrap();
+    let when = When::Zoned(Box::new(r.when().clone()), "Asia/Tokyo".parse().unwrap());
+    let json = serde_json::to_string(&when).unwrap();
+    assert_eq!(serde_json::from_str::<When>(&json).unwrap(), when);
+    // the cron is kept as the expression it was written as
+    assert!(json.contains("\"0 9 * * *\""), "{}", json);
+}
@@ -0,0 +1,50 @@
+use remember::shell;
+
+fn complete(line: &str, ids: &[u32]) -> (usize, Vec<String>) {
+    shell::complete(line, line.len(), ids)
+}
+
+#[test]
+fn commands_and_words() {
+    assert_eq!(
+        complete("", &[]).1,
+        ["remind", "list", "edit", "rm", "done", "snooze", "undo", "redo", "help", "quit"]
+    );
+    assert_eq!(
+        complete("re", &[]),
+        (0, vec![String::from("remind"), String::from("redo")])
+    );
+    assert_eq!(
+        complete("remind t", &[]),
+        (
+            7,
+            vec![
+                String::from("tue"),
+                String::from("thu"),
+                String::from("tz"),
+                String::from("timeout")
+            ]
+        )
+    );
+    assert_eq!(complete("remind 9:00 \"a\" urgency c", &[]).1, ["critical"]);
+    assert_eq!(complete("ls ", &[]).1, ["tz"]);
+    assert!(complete("remind 9:00 \"sa", &[]).1.is_empty());
+    assert!(complete("help ", &[]).1.is_empty());
+}
+
+#[test]
+fn ids() {
+    assert_eq!(complete("edit ", &[1, 2, 5]).1, ["1", "2", "5"]);
+    assert_eq!(complete("e 1", &[1, 4, 10, 11]).1, ["1", "10", "11"]);
+    assert_eq!(complete("rm 1", &[1, 4, 10, 11]).1, ["1", "10", "11"]);
+    assert_eq!(complete("snooze ", &[3]).1, ["3"]);
+    assert_eq!(complete("edit 1 w", &[1]).1, ["wed", "warn"]);
+}
+
+#[test]
+fn incomplete() {
+    assert!(shell::incomplete("remind 9:00 \"multi"));
+    assert!(shell::incomplete("remind 9:00 'a' 'b"));
+    assert!(!shell::incomplete("remind 9:00 \"multi\nline\""));
+    assert!(!shell::incomplete("list"));
+}
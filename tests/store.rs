@@ -0,0 +1,377 @@
+mod common;
+
+use std::{fs, path::PathBuf, thread, time::Duration};
+
+use chrono::{Local, TimeZone};
+use common::temp;
+use remember::{
+    control::{self, Control, Server},
+    notify::{Backend, Notice},
+    parser,
+    quiet::Action,
+    store::Store,
+};
+
+#[test]
+fn round_trip() {
+    let path = temp("round_trip").join("store");
+    let mut store = Store::open(path.clone()).unwrap();
+    assert!(store.is_empty());
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+
+    let add = |store: &mut Store, line: &str| control::handle(store, line, now).unwrap();
+    assert_eq!(add(&mut store, "add remind 9:00 \"standup\" warn 5m"), "1");
+    assert_eq!(
+        add(&mut store, "add remind mon 10:30 'say \"hi\"' \"body\""),
+        "2"
+    );
+    assert_eq!(
+        add(&mut store, "add remind 16/5/2100 11:25 \"dentist\""),
+        "3"
+    );
+    add(&mut store, "done 2");
+    add(&mut store, "snooze 3 1h");
+    add(&mut store, "rm 1");
+
+    let store = Store::open(path.clone()).unwrap();
+    let ids: Vec<u32> = store.iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, [2, 3]);
+    assert!(store.get(2).unwrap().is_done());
+    assert_eq!(store.get(2).unwrap().body(), Some("body"));
+    assert_eq!(
+        store.get(3).unwrap().snoozed(),
+        Some(now + chrono::Duration::hours(1))
+    );
+
+    // ids are never handed out twice, even after the newest one is removed
+    let mut store = store;
+    control::handle(&mut store, "rm 3", now).unwrap();
+    let mut store = Store::open(path).unwrap();
+    assert_eq!(
+        control::handle(&mut store, "add remind 30 \"x\"", now).unwrap(),
+        "4"
+    );
+}
+
+#[test]
+fn requests() {
+    let mut store = Store::new(None);
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut request = |line: &str| control::handle(&mut store, line, now);
+
+    request("add remind 10:00 \"later\"").unwrap();
+    request("add remind 9:30 \"sooner\"").unwrap();
+    request("edit 1 remind 11:00 \"later still\"").unwrap();
+    let list = request("list").unwrap();
+    let lines: Vec<&str> = list.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("2\t"), "{}", lines[0]);
+    assert!(
+        lines[1].ends_with("\tactive\tremind 11:00 \"later still\""),
+        "{}",
+        lines[1]
+    );
+
+    assert!(request("add remind 25:00 \"x\"")
+        .unwrap_err()
+        .contains("not between"));
+    assert_eq!(request("rm 9").unwrap_err(), "no reminder with id 9");
+    assert!(request("snooze 1 soon").is_err());
+    assert!(request("frobnicate").is_err());
+}
+
+#[test]
+fn socket() {
+    let dir = temp("socket");
+    let socket = dir.join("remember.sock");
+    let mut store = Store::open(dir.join("store")).unwrap();
+    let server = Server::bind(&socket).unwrap();
+    assert!(Server::bind(&socket).is_err());
+
+    let client = {
+        let socket = socket.clone();
+        thread::spawn(move || {
+            let mut control = Control::connect(socket, PathBuf::from("/nonexistent")).unwrap();
+            assert!(matches!(control, Control::Daemon(_)));
+            let id = control.request("add remind 9:00 \"standup\"").unwrap();
+            let list = control.request("list").unwrap();
+            (id, list, control.request("rm 7"))
+        })
+    };
+    while !client.is_finished() {
+        server.poll(&mut store).unwrap();
+        thread::sleep(Duration::from_millis(10));
+    }
+    let (id, list, rm) = client.join().unwrap();
+    assert_eq!(id, "1");
+    assert!(
+        list.ends_with("\tactive\tremind 9:00 \"standup\""),
+        "{}",
+        list
+    );
+    assert_eq!(rm, Err(String::from("no reminder with id 7")));
+    assert_eq!(store.len(), 1);
+
+    drop(server);
+    assert!(!socket.exists());
+    let control = Control::connect(socket, dir.join("store")).unwrap();
+    assert!(matches!(control, Control::Store(s, _) if s.len() == 1));
+}
+
+#[test]
+fn event_log() {
+    let path = temp("event_log").join("store");
+    let log = path.with_extension("log");
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut store = Store::open(path.clone()).unwrap();
+    control::handle(&mut store, "add remind 10:00 \"a\"", now).unwrap();
+    control::handle(&mut store, "add remind 11:00 \"b\"", now).unwrap();
+    control::handle(&mut store, "edit 1 remind 10:30 \"a\"", now).unwrap();
+    control::handle(&mut store, "snooze 2 1h", now).unwrap();
+    control::handle(&mut store, "done 2", now).unwrap();
+    control::handle(&mut store, "rm 1", now).unwrap();
+
+    // there is no snapshot until the log is compacted, the changes are only appended
+    assert!(!path.exists());
+    assert_eq!(fs::read_to_string(&log).unwrap().lines().count(), 6);
+    let kinds: Vec<String> = store.events().iter().map(|e| e.kind.to_string()).collect();
+    assert_eq!(
+        kinds,
+        [
+            "created",
+            "created",
+            "edited",
+            "snoozed",
+            "completed",
+            "removed"
+        ]
+    );
+
+    let mut store = Store::open(path.clone()).unwrap();
+    let ids: Vec<u32> = store.iter().map(|(id, _)| id).collect();
+    assert_eq!(ids, [2]);
+    assert!(store.get(2).unwrap().is_done());
+    assert_eq!(store.events().len(), 6);
+    assert_eq!(
+        control::handle(&mut store, "events 2", now)
+            .unwrap()
+            .lines()
+            .count(),
+        3
+    );
+
+    // a crash part way through writing an event
+    let mut text = fs::read_to_string(&log).unwrap();
+    text.push_str("7 2022-05-13T09:00:00+00:00 created 3 2022-05-");
+    fs::write(&log, text).unwrap();
+    let mut store = Store::open(path.clone()).unwrap();
+    assert_eq!(store.len(), 1);
+    assert_eq!(
+        control::handle(&mut store, "add remind 12:00 \"c\"", now).unwrap(),
+        "3"
+    );
+    // which is written over by a fresh snapshot
+    assert_eq!(fs::read_to_string(&log).unwrap(), "");
+    assert!(fs::read_to_string(&path).unwrap().contains("\nseq 7\n"));
+    let store = Store::open(path.clone()).unwrap();
+    assert_eq!(store.len(), 2);
+    assert!(store.events().is_empty());
+
+    // damage anywhere but the end is not a crash
+    fs::write(&log, "7 now created 9 -\n").unwrap();
+    assert!(Store::open(path).is_err());
+}
+
+#[test]
+fn compaction() {
+    let path = temp("compaction").join("store");
+    let log = path.with_extension("log");
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut store = Store::open(path.clone()).unwrap();
+    control::handle(&mut store, "add remind 10:00 \"a\"", now).unwrap();
+    for _ in 0..150 {
+        control::handle(&mut store, "snooze 1 1h", Local::now()).unwrap();
+        control::handle(&mut store, "edit 1 remind 10:00 \"a\"", now).unwrap();
+    }
+    assert!(fs::read_to_string(&log).unwrap().lines().count() < 200);
+
+    control::handle(&mut store, "add remind 11:00 \"b\"", now).unwrap();
+    assert!(fs::read_to_string(&path).unwrap().contains("\nseq "));
+
+    // a crash after the snapshot but before the log was emptied leaves events it already has
+    let text = fs::read_to_string(&log).unwrap();
+    fs::write(
+        &log,
+        format!("1 2022-05-13T09:00:00+00:00 removed 1 -\n{}", text),
+    )
+    .unwrap();
+    let store = Store::open(path).unwrap();
+    assert_eq!(store.iter().map(|(id, _)| id).collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn clients_without_a_daemon() {
+    let dir = temp("clients");
+    let socket = dir.join("remember.sock");
+    let connect = || Control::connect(socket.clone(), dir.join("store")).unwrap();
+    let (mut a, mut b) = (connect(), connect());
+    assert!(matches!(a, Control::Store(..)));
+
+    // each request reads in what the other one saved, so ids arent handed out twice
+    assert_eq!(a.request("add remind 9:00 \"a\"").unwrap(), "1");
+    assert_eq!(b.request("add remind 10:00 \"b\"").unwrap(), "2");
+    assert_eq!(a.request("list").unwrap().lines().count(), 2);
+    b.request("rm 1").unwrap();
+    assert_eq!(a.request("add remind 11:00 \"c\"").unwrap(), "3");
+    let store = Store::open(dir.join("store")).unwrap();
+    assert_eq!(store.iter().map(|(id, _)| id).collect::<Vec<_>>(), [2, 3]);
+}
+
+#[test]
+fn daemon_started_later() {
+    let dir = temp("started_later");
+    let socket = dir.join("remember.sock");
+    let mut control = Control::connect(socket.clone(), dir.join("store")).unwrap();
+    assert!(matches!(control, Control::Store(..)));
+
+    let mut store = Store::open(dir.join("store")).unwrap();
+    let lock = store.lock().unwrap();
+    let server = Server::bind(&socket).unwrap();
+    let client = thread::spawn(move || {
+        let id = control.request("add remind 9:00 \"standup\"");
+        (id, matches!(control, Control::Daemon(_)))
+    });
+    while !client.is_finished() {
+        server.poll(&mut store).unwrap();
+        thread::sleep(Duration::from_millis(10));
+    }
+    // the daemon has the store locked, so the request went to it
+    assert_eq!(client.join().unwrap(), (Ok(String::from("1")), true));
+    assert_eq!(store.len(), 1);
+
+    // and another daemon cant take it
+    let mut other = Store::open(dir.join("store")).unwrap();
+    assert!(other.try_lock().unwrap().is_none());
+    drop(lock);
+    assert!(other.try_lock().unwrap().is_some());
+}
+
+#[test]
+fn handles_in_turn() {
+    let path = temp("handles").join("store");
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let mut a = Store::open(path.clone()).unwrap();
+    let mut b = Store::open(path.clone()).unwrap();
+    let request = |store: &mut Store, line: &str| {
+        let _lock = store.lock().unwrap();
+        control::handle(store, line, now).unwrap()
+    };
+
+    assert_eq!(request(&mut a, "add remind 10:00 \"a\""), "1");
+    assert_eq!(request(&mut b, "add remind 11:00 \"b\""), "2");
+    // enough for a to compact, after which b still has to see everything
+    for _ in 0..120 {
+        request(&mut a, "snooze 1 1h");
+        request(&mut a, "snooze 1 2h");
+    }
+    assert!(fs::read_to_string(&path).unwrap().contains("\nseq "));
+    assert_eq!(request(&mut b, "add remind 12:00 \"c\""), "3");
+    request(&mut a, "done 3");
+
+    let store = Store::open(path.clone()).unwrap();
+    let entries: Vec<(u32, bool)> = store.iter().map(|(id, r)| (id, r.is_done())).collect();
+    assert_eq!(entries, [(1, false), (2, false), (3, true)]);
+
+    // a handle that didnt lock it is turned away rather than writing over what it missed
+    let mut stale = Store::open(path.clone()).unwrap();
+    request(&mut b, "rm 1");
+    assert!(control::handle(&mut stale, "add remind 13:00 \"d\"", now)
+        .unwrap_err()
+        .contains("changed by another remember"));
+    let store = Store::open(path).unwrap();
+    assert_eq!(store.iter().map(|(id, _)| id).collect::<Vec<_>>(), [2, 3]);
+}
+
+// keeps nothing, only a warning being shown matters
+struct Quiet;
+
+impl Backend for Quiet {
+    fn show(&mut self, _: &str, _: &Notice) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+#[test]
+fn warned_is_kept() {
+    let path = temp("warned_is_kept").join("store");
+    let mut store = Store::open(path.clone()).unwrap();
+    let now = Local::now();
+    let soon = now + chrono::Duration::minutes(30);
+    let line = format!("remind {} \"w\" warn 1h", soon.format("%-H:%M"));
+    let mut r = parser::parse_reminder(&line).unwrap();
+    // made well before the warning is due
+    r.restore(now - chrono::Duration::hours(2), None, None, None, 0, false);
+    let id = store.add(r);
+    store.save().unwrap();
+
+    let r = store.get_mut(id).unwrap();
+    assert_eq!(r.check_warn(now), Some(60 * 60));
+    r.deliver(&mut Quiet, Some(id), Some(60 * 60), None)
+        .unwrap();
+    store.save().unwrap();
+    assert_eq!(store.events().last().unwrap().kind.to_string(), "warned");
+
+    // a restarted daemon doesnt warn again
+    let store = Store::open(path.clone()).unwrap();
+    assert!(store.get(id).unwrap().warned().is_some());
+    assert_eq!(store.get(id).unwrap().check_warn(now), None);
+
+    // entries written before warned was kept still read
+    let log = path.with_extension("log");
+    fs::write(
+        &log,
+        "1 2022-05-13T09:00:00+00:00 created 1 2022-05-13T09:00:00+00:00 - - 2 active remind 9:00 \"old\"\n",
+    )
+    .unwrap();
+    let store = Store::open(path).unwrap();
+    assert_eq!(store.get(1).unwrap().count(), 2);
+    assert_eq!(store.get(1).unwrap().warned(), None);
+}
+
+// every notice fails to show
+struct Broken;
+
+impl Backend for Broken {
+    fn show(&mut self, _: &str, _: &Notice) -> Result<(), String> {
+        Err(String::from("no notification server"))
+    }
+}
+
+#[test]
+fn only_shown_notices_are_fired() {
+    let path = temp("only_shown_notices_are_fired").join("store");
+    let mut store = Store::open(path).unwrap();
+    let id = store.add(parser::parse_reminder("remind 9:00 \"x\"").unwrap());
+    store.save().unwrap();
+    let deliver = |store: &mut Store, backend: &mut dyn Backend, action| {
+        let r = store.get_mut(id).unwrap();
+        let _ = r.deliver(backend, Some(id), None, action);
+        store.save().unwrap();
+        store.events().last().unwrap().kind.to_string()
+    };
+
+    assert_eq!(deliver(&mut store, &mut Broken, None), "created");
+    assert_eq!(
+        deliver(&mut store, &mut Quiet, Some(Action::Defer)),
+        "created"
+    );
+    assert_eq!(
+        deliver(&mut store, &mut Quiet, Some(Action::Drop)),
+        "dropped"
+    );
+    assert_eq!(
+        deliver(&mut store, &mut Quiet, Some(Action::Silent)),
+        "fired"
+    );
+    assert_eq!(store.get(id).unwrap().count(), 1);
+}
@@ -0,0 +1,60 @@
+use chrono::{Local, TimeZone};
+use remember::{
+    notify::Urgency,
+    parser::{self, ParserError},
+    remind,
+    reminder::Priority,
+    Reminder, When,
+};
+
+#[test]
+fn statement() {
+    let r = parser::parse_reminder("remind 9:00 \"standup\" +work @oncall !high warn 5m").unwrap();
+    assert_eq!(r.tags(), ["+work", "@oncall"]);
+    assert_eq!(r.priority(), Priority::High);
+    assert_eq!(
+        r.to_string(),
+        "remind 9:00 \"standup\" !high +work @oncall warn 5m"
+    );
+    let again = parser::parse_reminder(&r.to_string()).unwrap();
+    assert_eq!(again.to_string(), r.to_string());
+
+    let r = parser::parse_reminder("remind mon 9:00 \"plan\" \"body\" +team-a/ops").unwrap();
+    assert_eq!(r.tags(), ["+team-a/ops"]);
+    assert_eq!(r.priority(), Priority::Normal);
+
+    let e = parser::parse_reminder("remind 9:00 \"x\" !urgent").unwrap_err();
+    assert!(matches!(e, ParserError::InvalidPriority(..)));
+    assert_eq!(e.loc().col, 17);
+}
+
+#[test]
+fn macro_and_tags() {
+    let r = remind!(9:00 "standup" +work @oncall !low);
+    assert_eq!(r.tags(), ["+work", "@oncall"]);
+    assert_eq!(r.priority(), Priority::Low);
+
+    let mut r = Reminder::new(When::Time(9, 0), String::from("x"), None);
+    r.set_tags(vec![
+        String::from("work"),
+        String::from("@home"),
+        String::from("+work"),
+    ]);
+    assert_eq!(r.tags(), ["+work", "@home"]);
+    assert!(r.has_tag("work"));
+    assert!(r.has_tag("+work"));
+    assert!(!r.has_tag("@work"));
+    assert!(r.has_tag("home"));
+    assert!(!r.has_tag("+home"));
+}
+
+#[test]
+fn priority_to_urgency() {
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 9, 0, 0).unwrap();
+    let urgency = |r: Reminder| r.notice(None, now, 1).urgency;
+    assert_eq!(urgency(remind!(9:00 "x" !high)), Urgency::Critical);
+    assert_eq!(urgency(remind!(9:00 "x" !low)), Urgency::Low);
+    assert_eq!(urgency(remind!(9:00 "x")), Urgency::Normal);
+    assert_eq!(urgency(remind!(9:00 "x" !high urgency low)), Urgency::Low);
+    assert!(Priority::High > Priority::Normal && Priority::Normal > Priority::Low);
+}
@@ -0,0 +1,79 @@
+use chrono::{Local, TimeZone};
+use remember::{parser::ParserError, todotxt, Reminder, When};
+
+#[test]
+fn tasks() {
+    let text = "(A) 2022-05-01 call mom +family @phone due:2022-05-16 at:18:30\n\
+x 2022-05-02 finished due:2022-05-10\n\
+\n\
+buy milk due:2022-05-20\n\
+2022-05-01 (B) not a priority due:2022-05-21\n";
+    let parsed = todotxt::import(text, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    assert_eq!(parsed.reminders.len(), 3);
+
+    // the priority and creation date are not part of the summary
+    let call = &parsed.reminders[0];
+    assert_eq!(call.summary(), "call mom");
+    assert_eq!(call.when(), &When::Date(16, 5, 2022, 18, 30));
+    assert_eq!(call.tags(), ["+family", "@phone"]);
+
+    // without at: it goes off at 9:00
+    let milk = &parsed.reminders[1];
+    assert_eq!(milk.summary(), "buy milk");
+    assert_eq!(milk.when(), &When::Date(20, 5, 2022, 9, 0));
+    assert!(milk.tags().is_empty());
+
+    // a priority only counts at the start of the line
+    assert_eq!(parsed.reminders[2].summary(), "(B) not a priority");
+}
+
+#[test]
+fn errors() {
+    let text = "no due date\n\
+pay rent due:2022-13-01\n\
+meet at:25:00 due:2022-05-20\n";
+    let parsed = todotxt::import(text, Some(String::from("todo.txt")));
+    assert!(parsed.reminders.is_empty());
+    let locs: Vec<(usize, usize)> = parsed
+        .errors
+        .iter()
+        .map(|e| (e.loc().row, e.loc().col))
+        .collect();
+    assert_eq!(locs, [(1, 1), (2, 10), (3, 6)]);
+    assert!(parsed
+        .errors
+        .iter()
+        .all(|e| matches!(e, ParserError::Unsupported(..))));
+    assert_eq!(
+        parsed.errors[1].to_string(),
+        "todo.txt:2:10: unsupported due date '2022-13-01'"
+    );
+}
+
+#[test]
+fn round_trip() {
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 12, 0, 0).unwrap();
+    let mut reminders = vec![
+        Reminder::new(
+            When::Date(16, 5, 2022, 11, 25),
+            String::from("dentist"),
+            None,
+        ),
+        Reminder::new(When::Time(9, 0), String::from("standup"), None),
+    ];
+    reminders[0].set_tags(vec![String::from("+health"), String::from("@town")]);
+
+    let exported = todotxt::export(&reminders, now);
+    assert_eq!(
+        exported,
+        "dentist +health @town due:2022-05-16 at:11:25\nstandup due:2022-05-14 at:09:00\n"
+    );
+    let parsed = todotxt::import(&exported, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    assert_eq!(parsed.reminders[0].summary(), "dentist");
+    assert_eq!(parsed.reminders[0].when(), reminders[0].when());
+    assert_eq!(parsed.reminders[0].tags(), reminders[0].tags());
+    // recurrence doesnt fit in todo.txt, it comes back as its next occurrence
+    assert_eq!(parsed.reminders[1].when(), &When::Date(14, 5, 2022, 9, 0));
+}
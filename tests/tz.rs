@@ -0,0 +1,84 @@
+use chrono::{DateTime, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use remember::{lexer::Lexer, parser, When};
+
+fn utc(y: i32, mon: u32, d: u32, h: u32, m: u32) -> DateTime<Local> {
+    Utc.with_ymd_and_hms(y, mon, d, h, m, 0)
+        .unwrap()
+        .with_timezone(&Local)
+}
+
+fn new_york(when: When) -> When {
+    When::Zoned(Box::new(when), "America/New_York".parse::<Tz>().unwrap())
+}
+
+#[test]
+fn time() {
+    let when = new_york(When::Time(9, 0));
+    assert_eq!(
+        when.next_fire_time(utc(2022, 5, 13, 12, 0)),
+        Some(utc(2022, 5, 13, 13, 0))
+    );
+}
+
+#[test]
+fn day_across_dst() {
+    // the clocks go forward on sunday the 13th of march
+    let when = new_york(When::Day(String::from("mon"), 9, 0));
+    assert_eq!(
+        when.next_fire_time(utc(2022, 3, 11, 0, 0)),
+        Some(utc(2022, 3, 14, 13, 0))
+    );
+    let when = new_york(When::Day(String::from("fri"), 9, 0));
+    assert_eq!(
+        when.next_fire_time(utc(2022, 3, 10, 0, 0)),
+        Some(utc(2022, 3, 11, 14, 0))
+    );
+}
+
+#[test]
+fn skipped_time() {
+    // 2:30 doesnt exist on the 13th of march, it goes off an hour later
+    let when = new_york(When::Time(2, 30));
+    assert_eq!(
+        when.next_fire_time(utc(2022, 3, 13, 5, 0)),
+        Some(utc(2022, 3, 13, 7, 30))
+    );
+}
+
+#[test]
+fn repeated_time() {
+    // 1:30 happens twice on the 6th of november, only the first goes off
+    let when = new_york(When::Time(1, 30));
+    assert_eq!(
+        when.next_fire_time(utc(2022, 11, 6, 4, 0)),
+        Some(utc(2022, 11, 6, 5, 30))
+    );
+    assert_eq!(
+        when.next_fire_time(utc(2022, 11, 6, 5, 30)),
+        Some(utc(2022, 11, 7, 6, 30))
+    );
+}
+
+#[test]
+fn date() {
+    let when = new_york(When::Date(16, 5, 2022, 11, 25));
+    assert_eq!(
+        when.next_fire_time(utc(2022, 5, 1, 0, 0)),
+        Some(utc(2022, 5, 16, 15, 25))
+    );
+}
+
+#[test]
+fn statement() {
+    let line = "mon 9:00 tz \"America/New_York\"";
+    let mut lexer = Lexer::new(line.chars(), None);
+    let when = parser::parse_when(&mut lexer).unwrap();
+    assert_eq!(when, new_york(When::Day(String::from("mon"), 9, 0)));
+    assert_eq!(when.to_string(), line);
+
+    let mut lexer = Lexer::new("9:00 tz \"Mars/Olympus_Mons\"".chars(), None);
+    let e = parser::parse_when(&mut lexer).unwrap_err();
+    assert!(matches!(e, parser::ParserError::InvalidZone(..)));
+    assert_eq!(e.loc().col, 9);
+}
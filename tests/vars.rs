@@ -0,0 +1,73 @@
+mod common;
+
+use std::fs;
+
+use chrono::{Local, TimeZone};
+use common::temp;
+use remember::{
+    parser::{self, ParserError},
+    template,
+};
+
+fn load(name: &str, text: &str) -> parser::Parsed {
+    let path = temp(name).join("reminders");
+    fs::write(&path, text).unwrap();
+    parser::parse_file(path).unwrap()
+}
+
+#[test]
+fn let_and_expand() {
+    let parsed = load(
+        "vars_let",
+        "let wiki = \"https://wiki.example.com\"\n\
+         let oncall = '${wiki}/oncall'\n\
+         remind mon 9:00 \"handover\" \"notes: ${oncall}\"\n",
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    assert_eq!(parsed.vars["oncall"], "https://wiki.example.com/oncall");
+    assert_eq!(
+        parsed.reminders[0].body(),
+        Some("notes: https://wiki.example.com/oncall")
+    );
+}
+
+#[test]
+fn builtins() {
+    let parsed = load(
+        "vars_builtins",
+        "remind 17:00 \"timesheet\" \"${weekday} ${date}, ${count} times\"\n",
+    );
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    // left in place until the reminder is shown
+    let body = parsed.reminders[0].body().unwrap();
+    assert_eq!(body, "${weekday} ${date}, ${count} times");
+
+    let now = Local.with_ymd_and_hms(2022, 5, 13, 17, 0, 0).unwrap();
+    let shown = template::expand(body, |name| template::builtin(name, now, 3)).unwrap();
+    assert_eq!(shown, "Friday 13/05/2022, 3 times");
+
+    // a let with a builtin's name replaces it
+    let parsed = load(
+        "vars_shadow",
+        "let date = \"today\"\nremind 9:00 \"${date}\"\n",
+    );
+    assert_eq!(parsed.reminders[0].summary(), "today");
+}
+
+#[test]
+fn unknown_var() {
+    let parsed = load(
+        "vars_unknown",
+        "remind 9:00 \"standup\"\nremind 9:00 \"héllo ${nope}\"\nremind 9:00 \"${later}\"\nlet later = \"x\"\n",
+    );
+    assert_eq!(parsed.reminders.len(), 1);
+    let [ParserError::UnknownVar(first, name), ParserError::UnknownVar(second, _)] =
+        &parsed.errors[..]
+    else {
+        panic!("{:?}", parsed.errors);
+    };
+    assert_eq!(name, "nope");
+    // the column of the $, counted in characters
+    assert_eq!((first.row, first.col), (2, 20));
+    assert_eq!((second.row, second.col), (3, 14));
+}
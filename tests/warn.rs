@@ -0,0 +1,58 @@
+use chrono::{DateTime, Duration, Local, TimeZone};
+use remember::{ical, lexer::Lexer, parser, remind, Reminder, When};
+
+fn local(y: i32, mon: u32, d: u32, h: u32, m: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(y, mon, d, h, m, 0).unwrap()
+}
+
+fn dentist() -> Reminder {
+    remind!(16/5/2100 11:25 "dentist" warn 15m, 1d)
+}
+
+#[test]
+fn check_warn() {
+    let r = dentist();
+    let due = local(2100, 5, 16, 11, 25);
+    assert_eq!(r.warn(), &[86400, 900]);
+    assert_eq!(r.check_warn(due - Duration::days(2)), None);
+    assert_eq!(r.check_warn(due - Duration::days(1)), Some(86400));
+    assert_eq!(r.check_warn(due - Duration::minutes(20)), Some(86400));
+    assert_eq!(r.check_warn(due - Duration::minutes(15)), Some(900));
+    assert_eq!(r.check_warn(due), None);
+}
+
+#[test]
+fn statement() {
+    let line = "remind 16/5/2100 11:25 \"dentist\" warn 15m, 1d";
+    let mut lexer = Lexer::new(line.chars(), None);
+    parser::get_command(&mut lexer);
+    let r = parser::parse_remind(&mut lexer, &parser::Vars::new()).unwrap();
+    assert_eq!(r.warn(), dentist().warn());
+    assert_eq!(
+        r.to_string(),
+        "remind 16/5/2100 11:25 \"dentist\" warn 1d, 15m"
+    );
+
+    let mut lexer = Lexer::new("remind 9:00 \"x\" warn 2h, 3y".chars(), None);
+    parser::get_command(&mut lexer);
+    let e = parser::parse_remind(&mut lexer, &parser::Vars::new()).unwrap_err();
+    assert!(matches!(e, parser::ParserError::InvalidDuration(..)));
+    assert_eq!(e.loc().col, 26);
+}
+
+#[test]
+fn lead_text() {
+    assert_eq!(remember::reminder::lead_text(900), "15 minutes");
+    assert_eq!(remember::reminder::lead_text(86400), "1 day");
+    assert_eq!(remember::reminder::lead_text(90), "90 seconds");
+}
+
+#[test]
+fn ical_round_trip() {
+    let exported = ical::export(&[dentist()], Local::now());
+    let parsed = ical::import(&exported, None);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    assert_eq!(parsed.reminders.len(), 1);
+    assert_eq!(parsed.reminders[0].when(), &When::Date(16, 5, 2100, 11, 25));
+    assert_eq!(parsed.reminders[0].warn(), &[86400, 900]);
+}